@@ -0,0 +1,181 @@
+//! Sealing and opening of message payloads, shared by off-chain clients and the test suite.
+//!
+//! Payloads are a NaCl `crypto_box` (X25519, XSalsa20-Poly1305) between the sender's secret key
+//! and the receiver's published key, byte compatible with tweetnacl's `nacl.box`. Either
+//! participant can open a message with their own secret key and the other participant's public key.
+
+use crypto_box::aead::{Aead, AeadCore, OsRng};
+use crypto_box::{Nonce, PublicKey, SalsaBox, SecretKey};
+
+use crate::{EncryptedPayload, MessageEntry, MAX_MESSAGE_LEN, MESSAGE_FORMAT_VERSION};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoError {
+    MessageTooLong,
+    UnsupportedVersion,
+    Deleted,
+    Encryption,
+    Decryption,
+}
+
+pub fn generate_secret_key() -> [u8; 32] {
+    SecretKey::generate(&mut OsRng).to_bytes()
+}
+
+// The value to publish with `set_encryption_key`
+pub fn public_key(secret_key: &[u8; 32]) -> [u8; 32] {
+    SecretKey::from(*secret_key).public_key().to_bytes()
+}
+
+pub fn seal(sender_secret: &[u8; 32], receiver_public: &[u8; 32], receiver_key_version: u16, plaintext: &[u8]) -> Result<EncryptedPayload, CryptoError> {
+    if plaintext.len() > MAX_MESSAGE_LEN {
+        return Err(CryptoError::MessageTooLong);
+    }
+
+    let nonce = SalsaBox::generate_nonce(&mut OsRng);
+    let ciphertext = shared_box(sender_secret, receiver_public)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| CryptoError::Encryption)?;
+
+    Ok(EncryptedPayload {
+        version: MESSAGE_FORMAT_VERSION,
+        key_version: receiver_key_version,
+        nonce: nonce.into(),
+        ciphertext,
+    })
+}
+
+// `their_public` is the other participant's key at the version recorded with the message
+pub fn open(own_secret: &[u8; 32], their_public: &[u8; 32], payload: &EncryptedPayload) -> Result<Vec<u8>, CryptoError> {
+    if payload.version != MESSAGE_FORMAT_VERSION {
+        return Err(CryptoError::UnsupportedVersion);
+    }
+
+    shared_box(own_secret, their_public)
+        .decrypt(Nonce::from_slice(&payload.nonce), payload.ciphertext.as_slice())
+        .map_err(|_| CryptoError::Decryption)
+}
+
+pub fn open_entry(own_secret: &[u8; 32], their_public: &[u8; 32], entry: &MessageEntry) -> Result<Vec<u8>, CryptoError> {
+    if entry.is_deleted() {
+        return Err(CryptoError::Deleted);
+    }
+    open(own_secret, their_public, &EncryptedPayload::from(entry))
+}
+
+fn shared_box(secret_key: &[u8; 32], public_key: &[u8; 32]) -> SalsaBox {
+    SalsaBox::new(&PublicKey::from(*public_key), &SecretKey::from(*secret_key))
+}
+
+impl From<&MessageEntry> for EncryptedPayload {
+    fn from(entry: &MessageEntry) -> Self {
+        EncryptedPayload {
+            version: entry.version,
+            key_version: entry.key_version,
+            nonce: entry.nonce,
+            ciphertext: entry.ciphertext[..entry.len as usize].to_vec(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+    use crate::MessagePage;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    fn key(s: &str) -> [u8; 32] {
+        hex(s).try_into().unwrap()
+    }
+
+    // Alice and Bob from NaCl's `tests/box.c`
+    const ALICE_SECRET: &str = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+    const ALICE_PUBLIC: &str = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+    const BOB_SECRET: &str = "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb";
+    const BOB_PUBLIC: &str = "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+
+    #[test]
+    fn public_keys_match_the_nacl_vector() {
+        assert_eq!(public_key(&key(ALICE_SECRET)), key(ALICE_PUBLIC));
+        assert_eq!(public_key(&key(BOB_SECRET)), key(BOB_PUBLIC));
+    }
+
+    // tweetnacl's `nacl.box` returns the tag followed by the ciphertext, which is what `box.out` lists
+    #[test]
+    fn opens_the_nacl_box_vector() {
+        let nonce = hex("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+        let plaintext = hex(concat!(
+            "be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffc",
+            "e5ecbaaf33bd751a1ac728d45e6c61296cdc3c01233561f41db66cce314adb31",
+            "0e3be8250c46f06dceea3a7fa1348057e2f6556ad6b1318a024a838f21af1fde",
+            "048977eb48f59ffd4924ca1c60902e52f0a089bc76897040e082f93776384864",
+            "5e0705",
+        ));
+        let ciphertext = hex(concat!(
+            "f3ffc7703f9400e52a7dfb4b3d3305d98e993b9f48681273c29650ba32fc76ce",
+            "48332ea7164d96a4476fb8c531a1186ac0dfc17c98dce87b4da7f011ec48c972",
+            "71d2c20f9b928fe2270d6fb863d51738b48eeee314a7cc8ab932164548e526ae",
+            "90224368517acfeabd6bb3732bc0e9da99832b61ca01b6de56244a9e88d5f9b3",
+            "7973f622a43d14a6599b1f654cb45a74e355a5",
+        ));
+
+        let sealed = shared_box(&key(ALICE_SECRET), &key(BOB_PUBLIC))
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .unwrap();
+        assert_eq!(sealed, ciphertext);
+
+        let payload = EncryptedPayload {
+            version: MESSAGE_FORMAT_VERSION,
+            key_version: 1,
+            nonce: nonce.try_into().unwrap(),
+            ciphertext,
+        };
+        assert_eq!(open(&key(BOB_SECRET), &key(ALICE_PUBLIC), &payload).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn either_participant_opens_a_sealed_message() {
+        let alice = generate_secret_key();
+        let bob = generate_secret_key();
+        let payload = seal(&alice, &public_key(&bob), 3, b"See you at eight").unwrap();
+        assert_eq!(payload.version, MESSAGE_FORMAT_VERSION);
+        assert_eq!(payload.key_version, 3);
+
+        assert_eq!(open(&bob, &public_key(&alice), &payload).unwrap(), b"See you at eight");
+        assert_eq!(open(&alice, &public_key(&bob), &payload).unwrap(), b"See you at eight");
+
+        let eve = generate_secret_key();
+        assert_eq!(open(&eve, &public_key(&alice), &payload), Err(CryptoError::Decryption));
+    }
+
+    #[test]
+    fn rejects_long_messages_and_unknown_versions() {
+        let alice = generate_secret_key();
+        let bob = generate_secret_key();
+        let too_long = [b'a'; MAX_MESSAGE_LEN + 1];
+        assert_eq!(seal(&alice, &public_key(&bob), 1, &too_long).err(), Some(CryptoError::MessageTooLong));
+
+        let mut payload = seal(&alice, &public_key(&bob), 1, b"Hi").unwrap();
+        payload.version += 1;
+        assert_eq!(open(&bob, &public_key(&alice), &payload), Err(CryptoError::UnsupportedVersion));
+    }
+
+    #[test]
+    fn open_entry_reads_page_entries() {
+        let alice = generate_secret_key();
+        let bob = generate_secret_key();
+        let payload = seal(&alice, &public_key(&bob), 1, b"Coffee?").unwrap();
+
+        let mut page: MessagePage = bytemuck::Zeroable::zeroed();
+        page.write(1, 0, 1_700_000_000, 1, &payload);
+        assert_eq!(open_entry(&bob, &public_key(&alice), &page.entries[1]).unwrap(), b"Coffee?");
+
+        // Unwritten slots and wiped messages read as deleted
+        assert_eq!(open_entry(&bob, &public_key(&alice), &page.entries[0]), Err(CryptoError::Deleted));
+        page.entry_mut(1).delete();
+        assert_eq!(open_entry(&bob, &public_key(&alice), &page.entries[1]), Err(CryptoError::Deleted));
+    }
+}
@@ -2,6 +2,12 @@
 #![allow(unexpected_cfgs)]
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+#[cfg(feature = "client")]
+pub mod crypto;
 
 declare_id!("GYR5dzGaxxccGV9Nd6RZy3jb8CktP9LC1fpWgwFUWhPR");
 
@@ -9,13 +15,176 @@ declare_id!("GYR5dzGaxxccGV9Nd6RZy3jb8CktP9LC1fpWgwFUWhPR");
 pub mod soldate {
     use super::*;
 
+    // One-time setup, only the program's upgrade authority can create the config and become its admin
+    pub fn initialize(ctx: Context<Initialize>, limits: Limits) -> Result<()> {
+        limits.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.authority.key();
+        config.guardian = ctx.accounts.authority.key();
+        config.limits = limits;
+        config.paused = 0;
+        config.super_like_price = 0;
+        config.premium_mint = Pubkey::default();
+        config.premium_price = 0;
+        config.premium_period = 0;
+        config.bump = ctx.bumps.config;
+        ctx.accounts.treasury.bump = ctx.bumps.treasury;
+        Ok(())
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, limits: Limits, new_admin: Option<Pubkey>) -> Result<()> {
+        limits.validate()?;
+
+        let config = &mut ctx.accounts.config;
+        config.limits = limits;
+        if let Some(new_admin) = new_admin {
+            config.admin = new_admin;
+        }
+        emit_config_updated(&ctx.accounts.admin, config)
+    }
+
+    pub fn set_guardian(ctx: Context<UpdateConfig>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.config.guardian = guardian;
+        emit_config_updated(&ctx.accounts.admin, &ctx.accounts.config)
+    }
+
+    pub fn set_super_like_price(ctx: Context<UpdateConfig>, price: u64) -> Result<()> {
+        ctx.accounts.config.super_like_price = price;
+        emit_config_updated(&ctx.accounts.admin, &ctx.accounts.config)
+    }
+
+    // Premium is sold in whole periods of one whitelisted SPL or Token-2022 mint
+    pub fn set_premium_plan(ctx: Context<UpdateConfig>, mint: Pubkey, price: u64, period: i64) -> Result<()> {
+        // A free plan would let anyone stack periods without limit
+        require!(price > 0, SolDateError::InvalidConfig);
+        require!(period > 0 && period <= MAX_PREMIUM_PERIOD, SolDateError::InvalidConfig);
+
+        let config = &mut ctx.accounts.config;
+        config.premium_mint = mint;
+        config.premium_price = price;
+        config.premium_period = period;
+        emit_config_updated(&ctx.accounts.admin, config)
+    }
+
+    // Buys one period, added on top of whatever premium time is left
+    pub fn purchase_premium(ctx: Context<PurchasePremium>) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_PREMIUM)?;
+        ctx.accounts.profile.require_good_standing()?;
+        let config = &ctx.accounts.config;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.buyer_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            config.premium_price,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let clock = Clock::get()?;
+        let profile = &mut ctx.accounts.profile;
+        profile.premium_until = profile.premium_until
+            .max(clock.unix_timestamp)
+            .checked_add(config.premium_period)
+            .ok_or(SolDateError::PremiumOverflow)?;
+
+        emit!(PremiumPurchased {
+            owner: profile.owner,
+            mint: config.premium_mint,
+            amount: config.premium_price,
+            premium_until: profile.premium_until,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn withdraw_treasury_tokens(ctx: Context<WithdrawTreasuryTokens>, amount: u64) -> Result<()> {
+        let bump = ctx.accounts.treasury.bump;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.treasury_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: ctx.accounts.treasury.to_account_info(),
+                },
+                &[&[b"treasury", &[bump]]],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let clock = Clock::get()?;
+        emit!(TreasuryWithdrawn {
+            admin: ctx.accounts.admin.key(),
+            recipient: ctx.accounts.recipient_token_account.key(),
+            amount,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // The treasury always keeps its rent-exempt minimum so the account survives withdrawals
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(treasury.data_len());
+        require!(treasury.lamports().saturating_sub(rent_exempt) >= amount, SolDateError::InsufficientTreasuryBalance);
+
+        treasury.sub_lamports(amount)?;
+        ctx.accounts.recipient.add_lamports(amount)?;
+
+        let clock = Clock::get()?;
+        emit!(TreasuryWithdrawn {
+            admin: ctx.accounts.admin.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // The guardian can only add pause bits, lifting a pause is left to the admin
+    pub fn set_paused(ctx: Context<SetPaused>, paused: u8) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let authority = ctx.accounts.authority.key();
+
+        if authority != config.admin {
+            require!(paused & config.paused == config.paused, SolDateError::Unauthorized);
+        }
+        config.paused = paused;
+
+        let clock = Clock::get()?;
+        emit!(PauseUpdated {
+            authority,
+            paused,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
     pub fn create_profile(ctx: Context<CreateProfile>, name: String, age: u8, bio: String, interests: Vec<String>, location: String) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_PROFILES)?;
         let profile = &mut ctx.accounts.profile;
+        let limits = &ctx.accounts.config.limits;
 
-        require!(name.len() <= 32, SolDateError::NameTooLong);
-        require!(bio.len() <= 100, SolDateError::BioTooLong);
-        require!(age >= 18, SolDateError::AgeTooYoung);
+        limits.check_name(&name)?;
+        limits.check_bio(&bio)?;
+        limits.check_age(age)?;
+        limits.check_interests(&interests)?;
+        limits.check_location(&location)?;
 
+        let clock = Clock::get()?;
         profile.owner = ctx.accounts.user.key();
         profile.name = name;
         profile.age = age;
@@ -23,270 +192,2648 @@ pub mod soldate {
         profile.interests = interests;
         profile.location = location;
         profile.is_active = true;
-        profile.created_at = Clock::get()?.unix_timestamp;
-        profile.matches = Vec::new();
+        profile.deactivated_at = None;
+        profile.created_at = clock.unix_timestamp;
+        profile.match_count = 0;
+        profile.likes_sent = 0;
+        profile.message_pages = 0;
+        profile.encryption_key = [0; 32];
+        profile.encryption_key_version = 0;
+        profile.moderation = ModerationStatus::Clear;
+        profile.moderation_until = None;
+        profile.like_day = 0;
+        profile.likes_today = 0;
+        profile.premium_until = 0;
         profile.bump = ctx.bumps.profile;
 
+        emit!(ProfileCreated {
+            owner: profile.owner,
+            profile: profile.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
         Ok(())
     }
 
     pub fn update_profile(ctx: Context<UpdateProfile>, name: Option<String>, age: Option<u8>, bio: Option<String>, interests: Option<Vec<String>>, location: Option<String>) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_PROFILES)?;
+        ctx.accounts.profile.require_good_standing()?;
         let profile = &mut ctx.accounts.profile;
+        let limits = &ctx.accounts.config.limits;
 
         if let Some(new_name) = name {
-            require!(new_name.len() <= 32, SolDateError::NameTooLong);
+            limits.check_name(&new_name)?;
             profile.name = new_name;
         }
 
         if let Some(new_bio) = bio {
-            require!(new_bio.len() <= 100, SolDateError::BioTooLong);
+            limits.check_bio(&new_bio)?;
             profile.bio = new_bio;
         }
         
         if let Some(new_age) = age {
-            require!(new_age >= 18, SolDateError::AgeTooYoung);
+            limits.check_age(new_age)?;
             profile.age = new_age;
         }
 
         if let Some(new_interests) = interests {
+            limits.check_interests(&new_interests)?;
             profile.interests = new_interests;
         }
 
         if let Some(new_location) = location {
+            limits.check_location(&new_location)?;
             profile.location = new_location;
         }
 
+        let clock = Clock::get()?;
+        emit!(ProfileUpdated {
+            owner: profile.owner,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
         Ok(())
     }
 
-    pub fn send_like(ctx: Context<SendLike>, target_user: Pubkey) -> Result<()> {
-        let like = &mut ctx.accounts.like;
-        let sender_profile = &mut ctx.accounts.sender_profile;
-        let target_profile = &mut ctx.accounts.target_profile;
+    pub fn deactivate_profile(ctx: Context<UpdateProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.profile;
 
-        require!(sender_profile.is_active, SolDateError::UserNotActive);
-        require!(target_profile.is_active, SolDateError::UserNotActive);
-        require!(ctx.accounts.sender.key() != target_user, SolDateError::CannotLikeSelf);
+        require!(profile.is_active, SolDateError::UserNotActive);
 
-        let timestamp = Clock::get()?.unix_timestamp;
-        let sender_key = ctx.accounts.sender.key();
+        // Matches are kept, the existing `is_active` checks hide the user from likes and messages
+        let clock = Clock::get()?;
+        profile.is_active = false;
+        profile.deactivated_at = Some(clock.unix_timestamp);
 
-        like.sender = sender_key;
-        like.receiver = target_user;
-        like.timestamp = timestamp;
-        like.bump = ctx.bumps.like;
+        emit!(ProfileDeactivated {
+            owner: profile.owner,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-        // Check if target user already liked sender (reverse like exists)
-        let reverse_like_seeds = [
-            b"like",
-            target_user.as_ref(),
-            sender_key.as_ref(),
-        ];
-        let (reverse_like_pda, _) = Pubkey::find_program_address(&reverse_like_seeds, ctx.program_id);
-        
-        let mut is_mutual = false;
-        if let Some(reverse_like_info) = ctx.remaining_accounts.get(0) {
-            if reverse_like_info.key() == reverse_like_pda && !reverse_like_info.data_is_empty() {
-                is_mutual = true;
-                
-                // Add to both users' matches
-                if !sender_profile.matches.contains(&target_user) {
-                    sender_profile.matches.push(target_user);
+    pub fn reactivate_profile(ctx: Context<UpdateProfile>) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_PROFILES)?;
+        ctx.accounts.profile.require_good_standing()?;
+        let profile = &mut ctx.accounts.profile;
+
+        require!(!profile.is_active, SolDateError::ProfileAlreadyActive);
+
+        profile.is_active = true;
+        profile.deactivated_at = None;
+
+        let clock = Clock::get()?;
+        emit!(ProfileReactivated {
+            owner: profile.owner,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Publishes a new X25519 public key, messages sealed to older keys keep their version tag
+    pub fn set_encryption_key(ctx: Context<UpdateProfile>, encryption_key: [u8; 32]) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_PROFILES)?;
+        let profile = &mut ctx.accounts.profile;
+
+        require!(encryption_key != [0; 32], SolDateError::EncryptionKeyMissing);
+
+        profile.encryption_key = encryption_key;
+        profile.encryption_key_version += 1;
+
+        let clock = Clock::get()?;
+        emit!(EncryptionKeyPublished {
+            owner: profile.owner,
+            encryption_key,
+            key_version: profile.encryption_key_version,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn close_profile<'info>(ctx: Context<'_, '_, 'info, 'info, CloseProfile<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        let owner = ctx.accounts.user.key();
+        let user_info = ctx.accounts.user.to_account_info();
+        let profile_key = ctx.accounts.profile.key();
+        let profile = &mut ctx.accounts.profile;
+        // A new profile at the same address would start out clear of any suspension or ban
+        profile.require_good_standing()?;
+
+        // Matches, sent likes and sent messages are cleaned up from remaining accounts
+        for account_info in ctx.remaining_accounts.iter() {
+            require!(account_info.key() != profile_key, SolDateError::InvalidAccount);
+
+            if let Ok(mut match_account) = Account::<Match>::try_from(account_info) {
+                let counterpart_key = match_account.other(&owner).ok_or(SolDateError::Unauthorized)?;
+                if match_account.status == MatchStatus::Active {
+                    // The counterpart profile has to be passed alongside its match
+                    let (counterpart_pda, _) = Pubkey::find_program_address(&[b"profile", counterpart_key.as_ref()], ctx.program_id);
+                    let counterpart_info = ctx.remaining_accounts
+                        .iter()
+                        .find(|info| info.key() == counterpart_pda)
+                        .ok_or(SolDateError::InvalidAccount)?;
+                    let mut counterpart = Account::<UserProfile>::try_from(counterpart_info)?;
+
+                    // And so does the counterpart's like, which no longer has a like to answer
+                    let (reverse_like_pda, _) = Pubkey::find_program_address(&[b"like", counterpart_key.as_ref(), owner.as_ref()], ctx.program_id);
+                    let reverse_like_info = ctx.remaining_accounts
+                        .iter()
+                        .find(|info| info.key() == reverse_like_pda)
+                        .ok_or(SolDateError::ReverseLikeRequired)?;
+                    let mut reverse_like = Account::<Like>::try_from(reverse_like_info)?;
+                    reverse_like.is_mutual = false;
+
+                    counterpart.match_count = counterpart.match_count.saturating_sub(1);
+                    profile.match_count = profile.match_count.saturating_sub(1);
+                    match_account.status = MatchStatus::Unmatched;
+
+                    counterpart.exit(ctx.program_id)?;
+                    reverse_like.exit(ctx.program_id)?;
+                    emit!(MatchEnded {
+                        user_a: match_account.user_a,
+                        user_b: match_account.user_b,
+                        ended_by: owner,
+                        timestamp: clock.unix_timestamp,
+                        slot: clock.slot,
+                    });
+                }
+
+                // Matches the owner paid for are closed, the others stay with their payer
+                if match_account.payer == owner {
+                    match_account.close(user_info.clone())?;
+                } else {
+                    match_account.exit(ctx.program_id)?;
+                }
+            } else if Account::<UserProfile>::try_from(account_info).is_ok() || Account::<Conversation>::try_from(account_info).is_ok() {
+                // Counterpart profiles are updated through their match, conversations once their pages are closed
+                continue;
+            } else if account_info.owner == &system_program::ID {
+                // Counterpart wallets get their message deposits back through the pages below
+                continue;
+            } else if let Ok(like) = Account::<Like>::try_from(account_info) {
+                if like.receiver == owner && like.sender != owner {
+                    // Likes received are updated through their match and stay with their sender
+                    continue;
                 }
-                if !target_profile.matches.contains(&sender_key) {
-                    target_profile.matches.push(sender_key);
+                require!(like.sender == owner, SolDateError::Unauthorized);
+                profile.likes_sent = profile.likes_sent.saturating_sub(1);
+                like.close(user_info.clone())?;
+            } else if let Ok(page) = AccountLoader::<MessagePage>::try_from(account_info) {
+                // Pages go whether or not they were read, a partly filled page is opened again by the next message
+                let (conversation_key, deposits) = {
+                    let page = page.load()?;
+                    require!(page.payer == owner, SolDateError::Unauthorized);
+                    (page.conversation, page.deposits)
+                };
+
+                // The conversation has to be passed alongside its pages
+                let conversation_info = ctx.remaining_accounts
+                    .iter()
+                    .find(|info| info.key() == conversation_key)
+                    .ok_or(SolDateError::InvalidAccount)?;
+                let mut conversation = Account::<Conversation>::try_from(conversation_info)?;
+                conversation.open_pages = conversation.open_pages.saturating_sub(1);
+                conversation.exit(ctx.program_id)?;
+
+                // So does the counterpart's wallet while the page holds deposits for their messages
+                let counterpart_index = 1 - conversation.participant_index(&owner)?;
+                let counterpart_deposits = deposits[counterpart_index as usize];
+                if counterpart_deposits > 0 {
+                    let counterpart_key = if counterpart_index == 0 { conversation.user_a } else { conversation.user_b };
+                    let counterpart_info = ctx.remaining_accounts
+                        .iter()
+                        .find(|info| info.key() == counterpart_key)
+                        .ok_or(SolDateError::InvalidAccount)?;
+                    page.sub_lamports(counterpart_deposits)?;
+                    counterpart_info.add_lamports(counterpart_deposits)?;
+                }
+
+                profile.message_pages = profile.message_pages.saturating_sub(1);
+                page.close(user_info.clone())?;
+            } else {
+                return err!(SolDateError::InvalidAccount);
+            }
+        }
+
+        // Conversations the owner opened go once all of their pages are closed
+        for account_info in ctx.remaining_accounts.iter() {
+            if let Ok(conversation) = Account::<Conversation>::try_from(account_info) {
+                if conversation.payer == owner && conversation.open_pages == 0 {
+                    conversation.close(user_info.clone())?;
                 }
             }
         }
 
-        like.is_mutual = is_mutual;
+        require!(profile.likes_sent == 0, SolDateError::OutstandingAccounts);
+        require!(profile.message_pages == 0, SolDateError::OutstandingAccounts);
+        require!(profile.match_count == 0, SolDateError::OutstandingAccounts);
+
+        emit!(ProfileClosed {
+            owner,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn send_like(ctx: Context<SendLike>, target_user: Pubkey) -> Result<()> {
+        like_user(ctx.accounts, &ctx.bumps, ctx.program_id, target_user, LikeKind::Normal)
+    }
+
+    // A like that stands out in the receiver's inbox, the fee set in the config goes to the treasury
+    pub fn send_super_like(ctx: Context<SendSuperLike>, target_user: Pubkey) -> Result<()> {
+        like_user(&mut ctx.accounts.base, &ctx.bumps.base, ctx.program_id, target_user, LikeKind::Super)?;
+
+        let price = ctx.accounts.base.config.super_like_price;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.base.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.base.sender.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            price,
+        )
+    }
+
+    pub fn unlike(ctx: Context<Unlike>, target_user: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        let sender_key = ctx.accounts.sender.key();
+        let sender_profile = &mut ctx.accounts.sender_profile;
+        let target_profile = &mut ctx.accounts.target_profile;
+
+        let match_info = ctx.accounts.match_account.to_account_info();
+        let active_match = load_unchecked::<Match>(&match_info, ctx.program_id)?
+            .filter(|match_account| match_account.status == MatchStatus::Active);
+        let reverse_like_info = ctx.accounts.reverse_like.to_account_info();
+        match load_unchecked::<Like>(&reverse_like_info, ctx.program_id)? {
+            Some(mut reverse_like) => {
+                reverse_like.is_mutual = false;
+                store_unchecked(&reverse_like_info, &reverse_like)?;
+            }
+            None => require!(active_match.is_none(), SolDateError::ReverseLikeRequired),
+        }
+
+        // Tear down the match if this like was one half of it
+        if let Some(mut match_account) = active_match {
+            match_account.status = MatchStatus::Unmatched;
+            store_unchecked(&match_info, &match_account)?;
+            sender_profile.match_count = sender_profile.match_count.saturating_sub(1);
+            target_profile.match_count = target_profile.match_count.saturating_sub(1);
+
+            emit!(MatchEnded {
+                user_a: match_account.user_a,
+                user_b: match_account.user_b,
+                ended_by: sender_key,
+                timestamp: clock.unix_timestamp,
+                slot: clock.slot,
+            });
+        }
+
+        // The like account itself is closed to the sender by the `close` constraint
+        sender_profile.likes_sent = sender_profile.likes_sent.saturating_sub(1);
+
+        emit!(LikeRemoved {
+            sender: sender_key,
+            receiver: target_user,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn unmatch(ctx: Context<Unmatch>, _other_user: Pubkey) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let other_profile = &mut ctx.accounts.other_profile;
+
+        // The `status` constraint already rejects pairs that are not matched
+        ctx.accounts.match_account.status = MatchStatus::Unmatched;
+        user_profile.match_count = user_profile.match_count.saturating_sub(1);
+        other_profile.match_count = other_profile.match_count.saturating_sub(1);
+
+        // Both likes are closed to their senders by the `close` constraints
+        user_profile.likes_sent = user_profile.likes_sent.saturating_sub(1);
+        other_profile.likes_sent = other_profile.likes_sent.saturating_sub(1);
+
+        let clock = Clock::get()?;
+        emit!(MatchEnded {
+            user_a: ctx.accounts.match_account.user_a,
+            user_b: ctx.accounts.match_account.user_b,
+            ended_by: ctx.accounts.user.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
         Ok(())
     }
 
     // Fixed messaging function with better memory management
-    pub fn send_message(ctx: Context<SendMessage>, _message_id: u64, content: String) -> Result<()> {
-        // Validate content length early to prevent large allocations
-        require!(content.len() <= 80, SolDateError::MessageTooLong); // Reduced even further
-        require!(ctx.accounts.sender_profile.is_active, SolDateError::UserNotActive);
-        require!(ctx.accounts.receiver_profile.is_active, SolDateError::UserNotActive);
-        
-        let sender = ctx.accounts.sender.key();
-        let receiver = ctx.accounts.receiver_profile.owner;
-        require!(sender != receiver, SolDateError::CannotMessageSelf);
+    pub fn send_message(ctx: Context<SendMessage>, payload: EncryptedPayload) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_MESSAGES)?;
+        ctx.accounts.sender_profile.require_good_standing()?;
+        // Validate the payload early to prevent large allocations
+        payload.validate(&ctx.accounts.config.limits, &ctx.accounts.sender_profile, &ctx.accounts.receiver_profile)?;
+        verify_can_message(
+            &ctx.accounts.sender_profile,
+            &ctx.accounts.receiver_profile,
+            &ctx.accounts.sender_block,
+            &ctx.accounts.receiver_block,
+            ctx.program_id,
+        )?;
+        // An active match proves both users liked each other
+        require_active_match(&ctx.accounts.match_account, ctx.program_id)?;
 
-        // Check permissions before any allocations
-        let sender_like_seeds = [b"like", sender.as_ref(), receiver.as_ref()];
-        let receiver_like_seeds = [b"like", receiver.as_ref(), sender.as_ref()];
-        
-        let (sender_like_pda, _) = Pubkey::find_program_address(&sender_like_seeds, ctx.program_id);
-        let (receiver_like_pda, _) = Pubkey::find_program_address(&receiver_like_seeds, ctx.program_id);
-        
-        let mut can_message = false;
-        
-        // Check if either like exists in remaining accounts
-        for account_info in ctx.remaining_accounts.iter() {
-            if (account_info.key() == sender_like_pda || account_info.key() == receiver_like_pda) 
-                && !account_info.data_is_empty() {
-                can_message = true;
-                break;
+        let opened_page = append_message(
+            &mut ctx.accounts.conversation,
+            ctx.bumps.conversation,
+            &ctx.accounts.page,
+            ctx.bumps.page,
+            &ctx.accounts.sender_profile,
+            ctx.accounts.receiver_profile.owner,
+            &payload,
+        )?;
+        if opened_page {
+            ctx.accounts.sender_profile.message_pages += 1;
+        }
+        pay_message_deposit(&ctx.accounts.sender, &ctx.accounts.page, &ctx.accounts.system_program)?;
+
+        Ok(())
+    }
+
+    // Sends SOL, or an SPL token when the mint and token accounts are passed, straight to the match.
+    // The note is stored in the event in plain text
+    pub fn send_tip(ctx: Context<SendTip>, amount: u64, note: Option<String>) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_TIPS)?;
+        ctx.accounts.sender_profile.require_good_standing()?;
+        require!(amount > 0, SolDateError::InvalidTipAmount);
+        if let Some(note) = &note {
+            require!(note.len() <= MAX_TIP_NOTE_LEN, SolDateError::TipNoteTooLong);
+        }
+        verify_can_message(
+            &ctx.accounts.sender_profile,
+            &ctx.accounts.receiver_profile,
+            &ctx.accounts.sender_block,
+            &ctx.accounts.receiver_block,
+            ctx.program_id,
+        )?;
+        require_active_match(&ctx.accounts.match_account, ctx.program_id)?;
+
+        let accounts = &ctx.accounts;
+        let mint = match (&accounts.mint, &accounts.sender_token_account, &accounts.receiver_token_account, &accounts.token_program) {
+            (Some(mint), Some(from), Some(to), Some(token_program)) => {
+                token_interface::transfer_checked(
+                    CpiContext::new(
+                        token_program.to_account_info(),
+                        TransferChecked {
+                            from: from.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: accounts.sender.to_account_info(),
+                        },
+                    ),
+                    amount,
+                    mint.decimals,
+                )?;
+                Some(mint.key())
+            }
+            (None, None, None, None) => {
+                system_program::transfer(
+                    CpiContext::new(
+                        accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: accounts.sender.to_account_info(),
+                            to: accounts.receiver.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+                None
             }
+            _ => return err!(SolDateError::InvalidAccount),
+        };
+
+        let clock = Clock::get()?;
+        emit!(TipSent {
+            sender: accounts.sender.key(),
+            receiver: accounts.receiver.key(),
+            mint,
+            amount,
+            note,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // A single message to someone the sender liked, before the like is returned. Kept on the
+    // conversation so taking the like back and liking again does not allow another one
+    pub fn send_intro_message(ctx: Context<SendIntroMessage>, payload: EncryptedPayload) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_MESSAGES)?;
+        ctx.accounts.sender_profile.require_good_standing()?;
+        payload.validate(&ctx.accounts.config.limits, &ctx.accounts.sender_profile, &ctx.accounts.receiver_profile)?;
+        verify_can_message(
+            &ctx.accounts.sender_profile,
+            &ctx.accounts.receiver_profile,
+            &ctx.accounts.sender_block,
+            &ctx.accounts.receiver_block,
+            ctx.program_id,
+        )?;
+
+        let opened_page = append_message(
+            &mut ctx.accounts.conversation,
+            ctx.bumps.conversation,
+            &ctx.accounts.page,
+            ctx.bumps.page,
+            &ctx.accounts.sender_profile,
+            ctx.accounts.receiver_profile.owner,
+            &payload,
+        )?;
+        if opened_page {
+            ctx.accounts.sender_profile.message_pages += 1;
         }
-        
-        require!(can_message, SolDateError::NoLikeExists);
+        pay_message_deposit(&ctx.accounts.sender, &ctx.accounts.page, &ctx.accounts.system_program)?;
+
+        // Checked after `append_message`, which sets up the pair of a new conversation
+        let conversation = &mut ctx.accounts.conversation;
+        let sender_index = conversation.participant_index(&ctx.accounts.sender.key())? as usize;
+        require!(!conversation.intro_sent[sender_index], SolDateError::IntroAlreadySent);
+        conversation.intro_sent[sender_index] = true;
+
+        Ok(())
+    }
+
+    // Moves the caller's read cursor past `seq`, the other participant's cursor is never touched.
+    // Not paused with messages, closing a page needs both cursors past it
+    pub fn mark_read(ctx: Context<MarkRead>, seq: u64) -> Result<()> {
+        let conversation = &mut ctx.accounts.conversation;
+        let reader = ctx.accounts.reader.key();
+
+        require!(seq < conversation.next_seq, SolDateError::InvalidSequence);
+        let cursor = match conversation.participant_index(&reader)? {
+            0 => &mut conversation.read_cursor_a,
+            _ => &mut conversation.read_cursor_b,
+        };
+        require!(seq >= *cursor, SolDateError::AlreadyRead);
+        *cursor = seq + 1;
+
+        let clock = Clock::get()?;
+        emit!(MessagesRead {
+            conversation: conversation.key(),
+            reader,
+            last_read_seq: seq,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn edit_message(ctx: Context<EditMessage>, seq: u64, payload: EncryptedPayload) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_MESSAGES)?;
+        ctx.accounts.sender_profile.require_good_standing()?;
+        payload.validate(&ctx.accounts.config.limits, &ctx.accounts.sender_profile, &ctx.accounts.receiver_profile)?;
+        verify_can_message(
+            &ctx.accounts.sender_profile,
+            &ctx.accounts.receiver_profile,
+            &ctx.accounts.sender_block,
+            &ctx.accounts.receiver_block,
+            ctx.program_id,
+        )?;
+        require!(seq < ctx.accounts.conversation.next_seq, SolDateError::InvalidSequence);
+
+        let sender_index = ctx.accounts.conversation.participant_index(&ctx.accounts.sender.key())?;
+        let mut page = ctx.accounts.page.load_mut()?;
+        let entry = page.entry_mut(seq);
+        require!(entry.sender_index == sender_index, SolDateError::Unauthorized);
+        require!(!entry.is_deleted(), SolDateError::MessageDeleted);
+
+        let clock = Clock::get()?;
+        entry.set_payload(&payload, ctx.accounts.sender_profile.encryption_key_version);
+        entry.edited_at = clock.unix_timestamp;
+
+        emit!(MessageEdited {
+            conversation: ctx.accounts.conversation.key(),
+            sender: ctx.accounts.sender.key(),
+            seq,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // The sender wipes the message for both sides and gets its deposit back, the receiver only hides it
+    // from their own view. The page rent goes back to the payer through `close_message_page`
+    pub fn delete_message(ctx: Context<DeleteMessage>, seq: u64) -> Result<()> {
+        require!(seq < ctx.accounts.conversation.next_seq, SolDateError::InvalidSequence);
+
+        let user_index = ctx.accounts.conversation.participant_index(&ctx.accounts.user.key())?;
+        let mut page = ctx.accounts.page.load_mut()?;
+        let deposit = page.slot_deposit;
+        let entry = page.entry_mut(seq);
+        require!(!entry.is_deleted(), SolDateError::MessageDeleted);
+
+        let by_sender = entry.sender_index == user_index;
+        if by_sender {
+            entry.delete();
+            page.deposits[user_index as usize] -= deposit;
+        } else {
+            entry.flags |= MessageEntry::HIDDEN_BY_RECEIVER;
+        }
+        drop(page);
+
+        if by_sender {
+            ctx.accounts.page.sub_lamports(deposit)?;
+            ctx.accounts.user.add_lamports(deposit)?;
+        }
+
+        let clock = Clock::get()?;
+        emit!(MessageRemoved {
+            conversation: ctx.accounts.conversation.key(),
+            user: ctx.accounts.user.key(),
+            seq,
+            by_sender,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Full pages can be closed by whoever opened them once both participants have read them
+    pub fn close_message_page(ctx: Context<CloseMessagePage>, page_index: u64) -> Result<()> {
+        let conversation = &ctx.accounts.conversation;
+        let page_end = MessagePage::end_seq(page_index);
+        require!(conversation.next_seq >= page_end, SolDateError::PageNotFull);
+        require!(conversation.read_cursor_a >= page_end, SolDateError::PageUnread);
+        require!(conversation.read_cursor_b >= page_end, SolDateError::PageUnread);
+
+        // The other participant's deposits go back to them, the payer's go with the rent when the
+        // `close` constraint closes the page
+        let other_index = conversation.participant_index(&ctx.accounts.other.key())?;
+        let other_deposits = ctx.accounts.page.load()?.deposits[other_index as usize];
+        ctx.accounts.page.sub_lamports(other_deposits)?;
+        ctx.accounts.other.add_lamports(other_deposits)?;
 
-        // Only allocate the message account after all validations pass
-        let message_account = &mut ctx.accounts.message;
-        let timestamp = Clock::get()?.unix_timestamp;
+        let payer_profile = &mut ctx.accounts.payer_profile;
+        payer_profile.message_pages = payer_profile.message_pages.saturating_sub(1);
+        let conversation = &mut ctx.accounts.conversation;
+        conversation.open_pages = conversation.open_pages.saturating_sub(1);
 
-        message_account.sender = sender;
-        message_account.receiver = receiver;
-        message_account.content = content;
-        message_account.timestamp = timestamp;
-        message_account.bump = ctx.bumps.message;
+        let clock = Clock::get()?;
+        emit!(MessagePageClosed {
+            conversation: ctx.accounts.conversation.key(),
+            payer: payer_profile.owner,
+            page_index,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Either participant can close a conversation once every page is gone, the rent goes back to its payer
+    pub fn close_conversation(ctx: Context<CloseConversation>) -> Result<()> {
+        let clock = Clock::get()?;
+        emit!(ConversationClosed {
+            conversation: ctx.accounts.conversation.key(),
+            closed_by: ctx.accounts.user.key(),
+            payer: ctx.accounts.payer.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
+    // Dissolved matches are kept for reuse until either user returns the rent to whoever paid it
+    pub fn close_match(ctx: Context<CloseMatch>, _other_user: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        emit!(MatchClosed {
+            user_a: ctx.accounts.match_account.user_a,
+            user_b: ctx.accounts.match_account.user_b,
+            closed_by: ctx.accounts.user.key(),
+            payer: ctx.accounts.payer.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
         Ok(())
     }
 
     pub fn block_user(ctx: Context<Blocked>, blocked_user: Pubkey) -> Result<()> {
         let block = &mut ctx.accounts.block;
+        let clock = Clock::get()?;
 
         block.blocker = ctx.accounts.blocker.key();
         block.blocked = blocked_user;
-        block.timestamp = Clock::get()?.unix_timestamp;
+        block.timestamp = clock.unix_timestamp;
         block.bump = ctx.bumps.block;
+
+        emit!(UserBlocked {
+            blocker: block.blocker,
+            blocked: blocked_user,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct CreateProfile<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+    pub fn unblock_user(ctx: Context<Unblock>, blocked_user: Pubkey) -> Result<()> {
+        // The block account itself is closed to the blocker by the `close` constraint
+        let clock = Clock::get()?;
+        emit!(UserUnblocked {
+            blocker: ctx.accounts.blocker.key(),
+            blocked: blocked_user,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-    #[account(
-        init,
-        payer = user,
-        space = 8 + UserProfile::INIT_SPACE,
-        seeds = [b"profile", user.key().as_ref()],
-        bump
-    )]
-    pub profile: Account<'info, UserProfile>,
-    pub system_program: Program<'info, System>,
-}
+    // One report per reporter and reported user, optionally pointing at a message in their conversation
+    pub fn report_user(ctx: Context<ReportUser>, reported_user: Pubkey, reason: ReportReason, message_seq: Option<u64>) -> Result<()> {
+        let reporter = ctx.accounts.reporter.key();
+        require!(reporter != reported_user, SolDateError::CannotReportSelf);
 
-#[derive(Accounts)]
-pub struct UpdateProfile<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+        let message = match message_seq {
+            Some(seq) => {
+                let conversation = ctx.accounts.conversation.as_ref().ok_or(SolDateError::InvalidAccount)?;
+                require!(seq < conversation.next_seq, SolDateError::InvalidSequence);
 
-    #[account(
-        mut,
-        seeds = [b"profile", user.key().as_ref()],
-        bump = profile.bump,
-    )]
-    pub profile: Account<'info, UserProfile>,
-    pub system_program: Program<'info, System>,
-}
+                // The message has to be one the reported user wrote
+                let page = ctx.accounts.page.as_ref().ok_or(SolDateError::InvalidAccount)?.load()?;
+                require!(page.conversation == conversation.key(), SolDateError::InvalidAccount);
+                require!(page.page_index == MessagePage::index_for(seq), SolDateError::InvalidAccount);
+                let entry = page.entry(seq);
+                require!(entry.timestamp != 0, SolDateError::InvalidSequence);
+                require!(entry.sender_index == conversation.participant_index(&reported_user)?, SolDateError::MessageNotFromReported);
+                Some(MessageRef { conversation: conversation.key(), seq })
+            }
+            None => None,
+        };
 
-#[derive(Accounts)]
-#[instruction(target_user: Pubkey)]
-pub struct SendLike<'info> {
-    #[account(mut)]
-    pub sender: Signer<'info>,
+        let clock = Clock::get()?;
+        let report = &mut ctx.accounts.report;
+        report.set_inner(Report {
+            reporter,
+            reported: reported_user,
+            reason,
+            message,
+            status: ReportStatus::Open,
+            created_at: clock.unix_timestamp,
+            updated_at: clock.unix_timestamp,
+            bump: ctx.bumps.report,
+        });
+        let reported_record = &mut ctx.accounts.reported_record;
+        reported_record.owner = reported_user;
+        reported_record.report_count += 1;
+        reported_record.bump = ctx.bumps.reported_record;
 
-    #[account(
-        mut,
-        seeds = [b"profile", sender.key().as_ref()],
-        bump = sender_profile.bump
-    )]
-    pub sender_profile: Account<'info, UserProfile>,
+        emit!(ReportFiled {
+            report: report.key(),
+            reporter,
+            reported: reported_user,
+            reason,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"profile", target_user.key().as_ref()],
-        bump = target_profile.bump
-    )]
-    pub target_profile: Account<'info, UserProfile>,
+    pub fn add_moderator(ctx: Context<AddModerator>, moderator: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        let record = &mut ctx.accounts.moderator_record;
+        record.moderator = moderator;
+        record.added_by = ctx.accounts.admin.key();
+        record.added_at = clock.unix_timestamp;
+        record.bump = ctx.bumps.moderator_record;
 
-    #[account(
-        init,
-        payer = sender,
-        space = 8 + Like::INIT_SPACE,
-        seeds = [b"like", sender.key().as_ref(), target_user.as_ref()],
-        bump
-    )]
-    pub like: Account<'info, Like>,
+        emit!(ModeratorAdded {
+            moderator,
+            admin: record.added_by,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    // The record is closed to the admin by the `close` constraint
+    pub fn remove_moderator(ctx: Context<RemoveModerator>, moderator: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        emit!(ModeratorRemoved {
+            moderator,
+            admin: ctx.accounts.admin.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(message_id: u64)]
-pub struct SendMessage<'info> {
-    #[account(mut)]
-    pub sender: Signer<'info>,
-    
-    #[account(
-        seeds = [b"profile", sender.key().as_ref()],
-        bump = sender_profile.bump
-    )]
-    pub sender_profile: Account<'info, UserProfile>,
-    
-    #[account(
-        seeds = [b"profile", receiver_profile.owner.as_ref()],
-        bump = receiver_profile.bump
-    )]
-    pub receiver_profile: Account<'info, UserProfile>,
+    // `until` of None keeps the status until a moderator clears it
+    pub fn suspend_profile(ctx: Context<ModerateProfile>, _user: Pubkey, until: Option<i64>) -> Result<()> {
+        moderate(ctx, ModerationStatus::Suspended, until)
+    }
 
-    #[account(
-        init,
-        payer = sender,
-        space = 8 + MessageAccount::INIT_SPACE,
-        seeds = [
-            b"message", 
-            sender.key().as_ref(),
-            receiver_profile.owner.as_ref(),
-            message_id.to_le_bytes().as_ref()
-        ],
-        bump
-    )]
-    pub message: Account<'info, MessageAccount>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn ban_profile(ctx: Context<ModerateProfile>, _user: Pubkey, until: Option<i64>) -> Result<()> {
+        moderate(ctx, ModerationStatus::Banned, until)
+    }
 
-#[derive(Accounts)]
-#[instruction(blocked_user: Pubkey)]
-pub struct Blocked<'info> {
-    #[account(mut)]
-    pub blocker: Signer<'info>,
-    #[account(
-        init,
-        payer = blocker,
+    pub fn clear_moderation(ctx: Context<ModerateProfile>, _user: Pubkey) -> Result<()> {
+        moderate(ctx, ModerationStatus::Clear, None)
+    }
+
+    pub fn update_report_status(ctx: Context<UpdateReport>, status: ReportStatus) -> Result<()> {
+        let clock = Clock::get()?;
+        let report = &mut ctx.accounts.report;
+        report.status = status;
+        report.updated_at = clock.unix_timestamp;
+
+        emit!(ReportStatusUpdated {
+            report: report.key(),
+            moderator: ctx.accounts.moderator.key(),
+            status,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Resolved reports can be closed by the reporter to get the rent back, the report count stays
+    pub fn close_report(ctx: Context<CloseReport>, _reported_user: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        let report = &ctx.accounts.report;
+        emit!(ReportClosed {
+            report: report.key(),
+            reporter: report.reporter,
+            reported: report.reported,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // The proposer's deposit goes into the escrow now, the invitee matches it on `accept_date`
+    pub fn propose_date(ctx: Context<ProposeDate>, invitee: Pubkey, date_time: i64, place: String, deposit: u64) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_DATES)?;
+        ctx.accounts.proposer_profile.require_good_standing()?;
+        verify_can_message(
+            &ctx.accounts.proposer_profile,
+            &ctx.accounts.invitee_profile,
+            &ctx.accounts.proposer_block,
+            &ctx.accounts.invitee_block,
+            ctx.program_id,
+        )?;
+        require_active_match(&ctx.accounts.match_account, ctx.program_id)?;
+        require!(place.len() <= MAX_DATE_PLACE_LEN, SolDateError::PlaceTooLong);
+        require!(deposit > 0, SolDateError::InvalidDeposit);
+
+        let clock = Clock::get()?;
+        require!(date_time > clock.unix_timestamp, SolDateError::InvalidDateTime);
+
+        let proposer = ctx.accounts.proposer.key();
+        ctx.accounts.date.set_inner(DateEscrow {
+            proposer,
+            invitee,
+            date_time,
+            place,
+            deposit,
+            status: DateStatus::Proposed,
+            proposer_attestation: None,
+            invitee_attestation: None,
+            outcome: None,
+            created_at: clock.unix_timestamp,
+            bump: ctx.bumps.date,
+        });
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.proposer.to_account_info(),
+                    to: ctx.accounts.date.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+
+        emit!(DateProposed {
+            date: ctx.accounts.date.key(),
+            proposer,
+            invitee,
+            date_time,
+            deposit,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn accept_date(ctx: Context<AcceptDate>) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_DATES)?;
+        ctx.accounts.invitee_profile.require_good_standing()?;
+        verify_can_message(
+            &ctx.accounts.invitee_profile,
+            &ctx.accounts.proposer_profile,
+            &ctx.accounts.invitee_block,
+            &ctx.accounts.proposer_block,
+            ctx.program_id,
+        )?;
+        require_active_match(&ctx.accounts.match_account, ctx.program_id)?;
+        let clock = Clock::get()?;
+        let date = &mut ctx.accounts.date;
+        require!(date.date_time > clock.unix_timestamp, SolDateError::InvalidDateTime);
+
+        date.status = DateStatus::Accepted;
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.invitee.to_account_info(),
+                    to: date.to_account_info(),
+                },
+            ),
+            date.deposit,
+        )?;
+
+        emit!(DateAccepted {
+            date: date.key(),
+            proposer: date.proposer,
+            invitee: date.invitee,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Either side can call off a date that was not accepted yet, the proposer gets everything back
+    pub fn cancel_date(ctx: Context<CancelDate>) -> Result<()> {
+        let clock = Clock::get()?;
+        emit!(DateCancelled {
+            date: ctx.accounts.date.key(),
+            cancelled_by: ctx.accounts.user.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Each participant states once whether the other one showed up
+    pub fn attest_date(ctx: Context<AttestDate>, other_showed_up: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        let date = &mut ctx.accounts.date;
+        require!(clock.unix_timestamp >= date.date_time, SolDateError::DateNotStarted);
+
+        let user = ctx.accounts.user.key();
+        let attestation = if user == date.proposer {
+            &mut date.proposer_attestation
+        } else {
+            &mut date.invitee_attestation
+        };
+        require!(attestation.is_none(), SolDateError::AlreadyAttested);
+        *attestation = Some(other_showed_up);
+
+        emit!(DateAttested {
+            date: date.key(),
+            user,
+            other_showed_up,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Anyone can settle once both sides attested or the attestation window ran out.
+    // The record stays behind as proof of the date until a participant closes it
+    pub fn resolve_date(ctx: Context<ResolveDate>) -> Result<()> {
+        let clock = Clock::get()?;
+        let date = &mut ctx.accounts.date;
+        let both_attested = date.proposer_attestation.is_some() && date.invitee_attestation.is_some();
+        require!(
+            both_attested || clock.unix_timestamp >= date.date_time.saturating_add(DATE_ATTESTATION_WINDOW),
+            SolDateError::DateNotResolvable
+        );
+
+        let outcome = date.settle();
+        let (proposer_payout, invitee_payout) = match outcome {
+            DateOutcome::ProposerNoShow => (0, 2 * date.deposit),
+            DateOutcome::InviteeNoShow => (2 * date.deposit, 0),
+            _ => (date.deposit, date.deposit),
+        };
+        date.sub_lamports(proposer_payout + invitee_payout)?;
+        ctx.accounts.proposer.add_lamports(proposer_payout)?;
+        ctx.accounts.invitee.add_lamports(invitee_payout)?;
+
+        date.status = DateStatus::Resolved;
+        date.outcome = Some(outcome);
+
+        emit!(DateResolved {
+            date: date.key(),
+            outcome,
+            proposer_payout,
+            invitee_payout,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // Returns the remaining rent to the proposer and frees the pair for another date.
+    // An attended date backs both reviews, so it stays until both are in or the review window ran out
+    pub fn close_date(ctx: Context<CloseDate>) -> Result<()> {
+        let clock = Clock::get()?;
+        let date = &ctx.accounts.date;
+        if date.outcome == Some(DateOutcome::Attended) {
+            let both_reviewed = account_exists(&ctx.accounts.proposer_review, ctx.program_id)
+                && account_exists(&ctx.accounts.invitee_review, ctx.program_id);
+            require!(
+                both_reviewed || clock.unix_timestamp >= date.date_time.saturating_add(DATE_REVIEW_WINDOW),
+                SolDateError::ReviewWindowOpen
+            );
+        }
+
+        emit!(DateClosed {
+            date: date.key(),
+            closed_by: ctx.accounts.user.key(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    // One review per reviewer and reviewee, backed by an attended date or an active match between them
+    pub fn submit_review(ctx: Context<SubmitReview>, reviewee: Pubkey, rating: Rating) -> Result<()> {
+        ctx.accounts.config.require_unpaused(Config::PAUSE_PROFILES)?;
+        ctx.accounts.reviewer_profile.require_good_standing()?;
+
+        let verified_date = match &ctx.accounts.date {
+            Some(date) => {
+                require!(date.outcome == Some(DateOutcome::Attended), SolDateError::NoDateOrMatch);
+                Some(date.key())
+            }
+            None => {
+                require!(
+                    ctx.accounts.match_account.as_ref().is_some_and(|match_account| match_account.status == MatchStatus::Active),
+                    SolDateError::NoDateOrMatch
+                );
+                None
+            }
+        };
+
+        let clock = Clock::get()?;
+        let reviewer = ctx.accounts.reviewer.key();
+        ctx.accounts.review.set_inner(Review {
+            reviewer,
+            reviewee,
+            rating,
+            date: verified_date,
+            created_at: clock.unix_timestamp,
+            bump: ctx.bumps.review,
+        });
+
+        let reviewee_record = &mut ctx.accounts.reviewee_record;
+        reviewee_record.owner = reviewee;
+        reviewee_record.reputation.record(&rating, verified_date.is_some());
+        reviewee_record.bump = ctx.bumps.reviewee_record;
+
+        emit!(ReviewSubmitted {
+            reviewer,
+            reviewee,
+            rating,
+            verified_date: verified_date.is_some(),
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+}
+
+// Every admin change to the config reports the resulting settings, `set_paused` has its own event
+fn emit_config_updated(admin: &Signer, config: &Config) -> Result<()> {
+    let clock = Clock::get()?;
+    emit!(ConfigUpdated {
+        updated_by: admin.key(),
+        admin: config.admin,
+        guardian: config.guardian,
+        limits: config.limits.clone(),
+        super_like_price: config.super_like_price,
+        premium_mint: config.premium_mint,
+        premium_price: config.premium_price,
+        premium_period: config.premium_period,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+    Ok(())
+}
+
+fn moderate(ctx: Context<ModerateProfile>, status: ModerationStatus, until: Option<i64>) -> Result<()> {
+    let clock = Clock::get()?;
+    if let Some(until) = until {
+        require!(until > clock.unix_timestamp, SolDateError::InvalidModerationExpiry);
+    }
+
+    let profile = &mut ctx.accounts.profile;
+    profile.moderation = status;
+    profile.moderation_until = until;
+
+    emit!(ProfileModerated {
+        user: profile.owner,
+        moderator: ctx.accounts.moderator.key(),
+        status,
+        until,
+        timestamp: clock.unix_timestamp,
+        slot: clock.slot,
+    });
+    Ok(())
+}
+
+// Shared by `send_like` and `send_super_like`, which only differ in the fee
+fn like_user(accounts: &mut SendLike, bumps: &SendLikeBumps, program_id: &Pubkey, target_user: Pubkey, kind: LikeKind) -> Result<()> {
+    accounts.config.require_unpaused(Config::PAUSE_LIKES)?;
+    accounts.sender_profile.require_good_standing()?;
+    let like = &mut accounts.like;
+    let sender_profile = &mut accounts.sender_profile;
+    let target_profile = &mut accounts.target_profile;
+
+    require!(sender_profile.is_active, SolDateError::UserNotActive);
+    require!(target_profile.is_active, SolDateError::UserNotActive);
+    require!(accounts.sender.key() != target_user, SolDateError::CannotLikeSelf);
+    require!(!account_exists(&accounts.sender_block, program_id), SolDateError::UserBlocked);
+    require!(!account_exists(&accounts.target_block, program_id), SolDateError::UserBlocked);
+
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+    let sender_key = accounts.sender.key();
+    sender_profile.record_daily_like(&accounts.config.limits, timestamp)?;
+
+    like.sender = sender_key;
+    like.receiver = target_user;
+    like.timestamp = timestamp;
+    like.bump = bumps.like;
+
+    // The reverse like only exists if the target user already liked the sender
+    let reverse_like_info = accounts.reverse_like.to_account_info();
+    let is_mutual = match load_unchecked::<Like>(&reverse_like_info, program_id)? {
+        Some(mut reverse_like) => {
+            reverse_like.is_mutual = true;
+            store_unchecked(&reverse_like_info, &reverse_like)?;
+            true
+        }
+        None => false,
+    };
+
+    match accounts.match_account.as_mut() {
+        Some(match_account) => {
+            require!(is_mutual, SolDateError::NotMutualLikes);
+            let max_matches = accounts.config.limits.max_matches;
+            require!(sender_profile.match_count < max_matches, SolDateError::MatchLimitReached);
+            require!(target_profile.match_count < max_matches, SolDateError::MatchLimitReached);
+
+            // A previously dissolved match between the pair is reused
+            let (user_a, user_b) = Match::pair(sender_key, target_user);
+            let payer = if match_account.user_a == Pubkey::default() { sender_key } else { match_account.payer };
+            match_account.set_inner(Match {
+                user_a,
+                user_b,
+                initiator: target_user,
+                payer,
+                matched_at: timestamp,
+                status: MatchStatus::Active,
+                bump: bumps.match_account.ok_or(SolDateError::MatchAccountRequired)?,
+            });
+
+            sender_profile.match_count += 1;
+            target_profile.match_count += 1;
+
+            emit!(MatchCreated {
+                user_a,
+                user_b,
+                initiator: target_user,
+                timestamp,
+                slot: clock.slot,
+            });
+        }
+        None => require!(!is_mutual, SolDateError::MatchAccountRequired),
+    }
+
+    like.is_mutual = is_mutual;
+    like.kind = kind;
+    sender_profile.likes_sent += 1;
+
+    emit!(LikeSent {
+        sender: sender_key,
+        receiver: target_user,
+        is_mutual,
+        kind,
+        timestamp,
+        slot: clock.slot,
+    });
+    Ok(())
+}
+
+// A seeds-verified PDA only exists once its init instruction has run, e.g. a block record
+fn account_exists(account: &AccountInfo, program_id: &Pubkey) -> bool {
+    account.owner == program_id && !account.data_is_empty()
+}
+
+// Taken unchecked so a pair that never matched fails with `NotMutualLikes` rather than a missing account
+fn require_active_match(match_account: &AccountInfo, program_id: &Pubkey) -> Result<()> {
+    let match_account = load_unchecked::<Match>(match_account, program_id)?;
+    require!(
+        match_account.is_some_and(|match_account| match_account.status == MatchStatus::Active),
+        SolDateError::NotMutualLikes
+    );
+    Ok(())
+}
+
+// Reverse likes and matches are passed unchecked so a client cannot hide one by leaving the account out
+fn load_unchecked<T: AccountDeserialize>(info: &AccountInfo, program_id: &Pubkey) -> Result<Option<T>> {
+    if !account_exists(info, program_id) {
+        return Ok(None);
+    }
+    Ok(Some(T::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+fn store_unchecked<T: AccountSerialize>(info: &AccountInfo, account: &T) -> Result<()> {
+    account.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+// Checks shared by every path that lets one user reach another
+fn verify_can_message(sender_profile: &UserProfile, receiver_profile: &UserProfile, sender_block: &AccountInfo, receiver_block: &AccountInfo, program_id: &Pubkey) -> Result<()> {
+    require!(sender_profile.is_active, SolDateError::UserNotActive);
+    require!(receiver_profile.is_active, SolDateError::UserNotActive);
+    require!(sender_profile.owner != receiver_profile.owner, SolDateError::CannotMessageSelf);
+    require!(!account_exists(sender_block, program_id), SolDateError::UserBlocked);
+    require!(!account_exists(receiver_block, program_id), SolDateError::UserBlocked);
+    Ok(())
+}
+
+// Writes a message into the conversation's current page, returns true if the page was just opened
+fn append_message(conversation: &mut Account<Conversation>, conversation_bump: u8, page: &AccountLoader<MessagePage>, page_bump: u8, sender_profile: &UserProfile, receiver: Pubkey, payload: &EncryptedPayload) -> Result<bool> {
+    let sender = sender_profile.owner;
+    let clock = Clock::get()?;
+    let timestamp = clock.unix_timestamp;
+    let seq = conversation.record_message(sender, receiver, timestamp, conversation_bump)?;
+    let sender_index = conversation.participant_index(&sender)?;
+
+    // `init_if_needed` leaves a new page without its discriminator until exit
+    let mut page = page.load_init().or_else(|_| page.load_mut())?;
+    let opened = page.conversation == Pubkey::default();
+    if opened {
+        page.conversation = conversation.key();
+        page.payer = sender;
+        page.page_index = MessagePage::index_for(seq);
+        page.slot_deposit = Rent::get()?.minimum_balance(8 + std::mem::size_of::<MessagePage>()) / MESSAGES_PER_PAGE as u64;
+        page.bump = page_bump;
+        conversation.open_pages += 1;
+    }
+    page.write(seq, sender_index, timestamp, sender_profile.encryption_key_version, payload);
+    let deposit = page.slot_deposit;
+    page.deposits[sender_index as usize] += deposit;
+
+    emit!(MessageSent {
+        conversation: conversation.key(),
+        sender,
+        receiver,
+        seq,
+        timestamp,
+        slot: clock.slot,
+    });
+
+    Ok(opened)
+}
+
+// Moves the deposit `append_message` booked for the latest message from the sender into its page
+fn pay_message_deposit<'info>(sender: &Signer<'info>, page: &AccountLoader<'info, MessagePage>, system_program: &Program<'info, System>) -> Result<()> {
+    // A page opened in this instruction has no discriminator yet, see `append_message`
+    let deposit = page.load_init().or_else(|_| page.load_mut())?.slot_deposit;
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: sender.to_account_info(),
+                to: page.to_account_info(),
+            },
+        ),
+        deposit,
+    )
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ SolDateError::InvalidAccount)]
+    pub program: Program<'info, crate::program::Soldate>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ SolDateError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin @ SolDateError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ SolDateError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PurchasePremium<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", buyer.key().as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+
+    #[account(address = config.premium_mint @ SolDateError::UnsupportedPremiumMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = buyer,
+        token::token_program = token_program
+    )]
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasuryTokens<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ SolDateError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = authority.key() == config.admin || authority.key() == config.guardian @ SolDateError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UserProfile::INIT_SPACE,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, UserProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        constraint = profile.owner == user.key() @ SolDateError::Unauthorized,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = profile.bump,
+    )]
+    pub profile: Account<'info, UserProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_user: Pubkey)]
+pub struct SendLike<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", sender.key().as_ref()],
+        bump = sender_profile.bump
+    )]
+    pub sender_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", target_user.key().as_ref()],
+        bump = target_profile.bump
+    )]
+    pub target_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + Like::INIT_SPACE,
+        seeds = [b"like", sender.key().as_ref(), target_user.as_ref()],
+        bump
+    )]
+    pub like: Account<'info, Like>,
+
+    /// CHECK: Deserialized as a `Like` when it exists, address is verified by the seeds
+    #[account(
+        mut,
+        seeds = [b"like", target_user.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub reverse_like: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", sender.key().as_ref(), target_user.as_ref()],
+        bump
+    )]
+    pub sender_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", target_user.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub target_block: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + Match::INIT_SPACE,
+        seeds = [
+            b"match",
+            std::cmp::min(sender.key(), target_user).as_ref(),
+            std::cmp::max(sender.key(), target_user).as_ref()
+        ],
+        bump
+    )]
+    pub match_account: Option<Account<'info, Match>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SendSuperLike<'info> {
+    pub base: SendLike<'info>,
+
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+}
+
+#[derive(Accounts)]
+#[instruction(target_user: Pubkey)]
+pub struct Unlike<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", sender.key().as_ref()],
+        bump = sender_profile.bump
+    )]
+    pub sender_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", target_user.as_ref()],
+        bump = target_profile.bump
+    )]
+    pub target_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"like", sender.key().as_ref(), target_user.as_ref()],
+        bump = like.bump
+    )]
+    pub like: Account<'info, Like>,
+
+    /// CHECK: Deserialized as a `Like` when it exists, address is verified by the seeds
+    #[account(
+        mut,
+        seeds = [b"like", target_user.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub reverse_like: UncheckedAccount<'info>,
+
+    /// CHECK: Deserialized as a `Match` when it exists, address is verified by the seeds
+    #[account(
+        mut,
+        seeds = [
+            b"match",
+            std::cmp::min(sender.key(), target_user).as_ref(),
+            std::cmp::max(sender.key(), target_user).as_ref()
+        ],
+        bump
+    )]
+    pub match_account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(other_user: Pubkey)]
+pub struct Unmatch<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, address = other_user)]
+    pub other: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", other_user.as_ref()],
+        bump = other_profile.bump
+    )]
+    pub other_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"like", user.key().as_ref(), other_user.as_ref()],
+        bump = user_like.bump
+    )]
+    pub user_like: Account<'info, Like>,
+
+    #[account(
+        mut,
+        close = other,
+        seeds = [b"like", other_user.as_ref(), user.key().as_ref()],
+        bump = other_like.bump
+    )]
+    pub other_like: Account<'info, Like>,
+
+    #[account(
+        mut,
+        constraint = match_account.status == MatchStatus::Active @ SolDateError::MatchNotActive,
+        seeds = [
+            b"match",
+            std::cmp::min(user.key(), other_user).as_ref(),
+            std::cmp::max(user.key(), other_user).as_ref()
+        ],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, Match>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SendMessage<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"profile", sender.key().as_ref()],
+        bump = sender_profile.bump
+    )]
+    pub sender_profile: Account<'info, UserProfile>,
+    
+    #[account(
+        seeds = [b"profile", receiver_profile.owner.as_ref()],
+        bump = receiver_profile.bump
+    )]
+    pub receiver_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + Conversation::INIT_SPACE,
+        seeds = [
+            b"conversation",
+            std::cmp::min(sender.key(), receiver_profile.owner).as_ref(),
+            std::cmp::max(sender.key(), receiver_profile.owner).as_ref()
+        ],
+        bump
+    )]
+    pub conversation: Account<'info, Conversation>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + std::mem::size_of::<MessagePage>(),
+        seeds = [
+            b"page",
+            conversation.key().as_ref(),
+            MessagePage::index_for(conversation.next_seq).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub page: AccountLoader<'info, MessagePage>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", sender.key().as_ref(), receiver_profile.owner.as_ref()],
+        bump
+    )]
+    pub sender_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", receiver_profile.owner.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub receiver_block: UncheckedAccount<'info>,
+
+    /// CHECK: Must hold an active `Match`, see `require_active_match`, address is verified by the seeds
+    #[account(
+        seeds = [
+            b"match",
+            std::cmp::min(sender.key(), receiver_profile.owner).as_ref(),
+            std::cmp::max(sender.key(), receiver_profile.owner).as_ref()
+        ],
+        bump
+    )]
+    pub match_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SendTip<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"profile", sender.key().as_ref()],
+        bump = sender_profile.bump
+    )]
+    pub sender_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"profile", receiver_profile.owner.as_ref()],
+        bump = receiver_profile.bump
+    )]
+    pub receiver_profile: Account<'info, UserProfile>,
+
+    #[account(mut, address = receiver_profile.owner @ SolDateError::InvalidUser)]
+    pub receiver: SystemAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", sender.key().as_ref(), receiver_profile.owner.as_ref()],
+        bump
+    )]
+    pub sender_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", receiver_profile.owner.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub receiver_block: UncheckedAccount<'info>,
+
+    /// CHECK: Must hold an active `Match`, see `require_active_match`, address is verified by the seeds
+    #[account(
+        seeds = [
+            b"match",
+            std::cmp::min(sender.key(), receiver_profile.owner).as_ref(),
+            std::cmp::max(sender.key(), receiver_profile.owner).as_ref()
+        ],
+        bump
+    )]
+    pub match_account: UncheckedAccount<'info>,
+
+    // SPL tips only, all four are left out for a SOL tip
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = sender,
+        token::token_program = token_program
+    )]
+    pub sender_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = receiver,
+        token::token_program = token_program
+    )]
+    pub receiver_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SendIntroMessage<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [b"profile", sender.key().as_ref()],
+        bump = sender_profile.bump
+    )]
+    pub sender_profile: Account<'info, UserProfile>,
+    
+    #[account(
+        seeds = [b"profile", receiver_profile.owner.as_ref()],
+        bump = receiver_profile.bump
+    )]
+    pub receiver_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + Conversation::INIT_SPACE,
+        seeds = [
+            b"conversation",
+            std::cmp::min(sender.key(), receiver_profile.owner).as_ref(),
+            std::cmp::max(sender.key(), receiver_profile.owner).as_ref()
+        ],
+        bump
+    )]
+    pub conversation: Account<'info, Conversation>,
+
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + std::mem::size_of::<MessagePage>(),
+        seeds = [
+            b"page",
+            conversation.key().as_ref(),
+            MessagePage::index_for(conversation.next_seq).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub page: AccountLoader<'info, MessagePage>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", sender.key().as_ref(), receiver_profile.owner.as_ref()],
+        bump
+    )]
+    pub sender_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", receiver_profile.owner.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub receiver_block: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"like", sender.key().as_ref(), receiver_profile.owner.as_ref()],
+        bump = like.bump
+    )]
+    pub like: Account<'info, Like>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seq: u64)]
+pub struct EditMessage<'info> {
+    pub sender: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"profile", sender.key().as_ref()],
+        bump = sender_profile.bump
+    )]
+    pub sender_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"profile", receiver_profile.owner.as_ref()],
+        bump = receiver_profile.bump
+    )]
+    pub receiver_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [
+            b"conversation",
+            std::cmp::min(sender.key(), receiver_profile.owner).as_ref(),
+            std::cmp::max(sender.key(), receiver_profile.owner).as_ref()
+        ],
+        bump = conversation.bump
+    )]
+    pub conversation: Account<'info, Conversation>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"page",
+            conversation.key().as_ref(),
+            MessagePage::index_for(seq).to_le_bytes().as_ref()
+        ],
+        bump = page.load()?.bump
+    )]
+    pub page: AccountLoader<'info, MessagePage>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", sender.key().as_ref(), receiver_profile.owner.as_ref()],
+        bump
+    )]
+    pub sender_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", receiver_profile.owner.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub receiver_block: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seq: u64)]
+pub struct DeleteMessage<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub conversation: Account<'info, Conversation>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"page",
+            conversation.key().as_ref(),
+            MessagePage::index_for(seq).to_le_bytes().as_ref()
+        ],
+        bump = page.load()?.bump
+    )]
+    pub page: AccountLoader<'info, MessagePage>,
+}
+
+#[derive(Accounts)]
+pub struct MarkRead<'info> {
+    pub reader: Signer<'info>,
+
+    #[account(mut)]
+    pub conversation: Account<'info, Conversation>,
+}
+
+#[derive(Accounts)]
+#[instruction(page_index: u64)]
+pub struct CloseMessagePage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", payer.key().as_ref()],
+        bump = payer_profile.bump
+    )]
+    pub payer_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub conversation: Account<'info, Conversation>,
+
+    #[account(
+        mut,
+        constraint = other.key() != payer.key() @ SolDateError::InvalidAccount,
+        constraint = conversation.participant_index(&other.key()).is_ok() @ SolDateError::InvalidAccount
+    )]
+    pub other: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        constraint = page.load()?.payer == payer.key() @ SolDateError::Unauthorized,
+        seeds = [b"page", conversation.key().as_ref(), page_index.to_le_bytes().as_ref()],
+        bump = page.load()?.bump
+    )]
+    pub page: AccountLoader<'info, MessagePage>,
+}
+
+#[derive(Accounts)]
+pub struct CloseConversation<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ SolDateError::InvalidAccount,
+        constraint = conversation.participant_index(&user.key()).is_ok() @ SolDateError::Unauthorized,
+        constraint = conversation.open_pages == 0 @ SolDateError::ConversationHasPages,
+        seeds = [b"conversation", conversation.user_a.as_ref(), conversation.user_b.as_ref()],
+        bump = conversation.bump
+    )]
+    pub conversation: Account<'info, Conversation>,
+}
+
+#[derive(Accounts)]
+#[instruction(other_user: Pubkey)]
+pub struct CloseMatch<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ SolDateError::InvalidAccount,
+        constraint = match_account.status == MatchStatus::Unmatched @ SolDateError::MatchStillActive,
+        seeds = [
+            b"match",
+            std::cmp::min(user.key(), other_user).as_ref(),
+            std::cmp::max(user.key(), other_user).as_ref()
+        ],
+        bump = match_account.bump
+    )]
+    pub match_account: Account<'info, Match>,
+}
+
+#[derive(Accounts)]
+#[instruction(blocked_user: Pubkey)]
+pub struct Blocked<'info> {
+    #[account(mut)]
+    pub blocker: Signer<'info>,
+    #[account(
+        init,
+        payer = blocker,
         space = 8 + BlockedUser::INIT_SPACE,
         seeds = [b"block", blocker.key().as_ref(), blocked_user.as_ref()],
         bump
     )]
-    pub block: Account<'info, BlockedUser>,
+    pub block: Account<'info, BlockedUser>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(blocked_user: Pubkey)]
+pub struct Unblock<'info> {
+    #[account(mut)]
+    pub blocker: Signer<'info>,
+    #[account(
+        mut,
+        close = blocker,
+        has_one = blocker @ SolDateError::Unauthorized,
+        seeds = [b"block", blocker.key().as_ref(), blocked_user.as_ref()],
+        bump = block.bump
+    )]
+    pub block: Account<'info, BlockedUser>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(reported_user: Pubkey)]
+pub struct ReportUser<'info> {
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    #[account(
+        seeds = [b"profile", reporter.key().as_ref()],
+        bump = reporter_profile.bump
+    )]
+    pub reporter_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"profile", reported_user.as_ref()],
+        bump = reported_profile.bump
+    )]
+    pub reported_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = reporter,
+        space = 8 + UserRecord::INIT_SPACE,
+        seeds = [b"record", reported_user.as_ref()],
+        bump
+    )]
+    pub reported_record: Account<'info, UserRecord>,
+
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + Report::INIT_SPACE,
+        seeds = [b"report", reporter.key().as_ref(), reported_user.as_ref()],
+        bump
+    )]
+    pub report: Account<'info, Report>,
+
+    // Only needed when the report points at a message
+    #[account(
+        seeds = [
+            b"conversation",
+            std::cmp::min(reporter.key(), reported_user).as_ref(),
+            std::cmp::max(reporter.key(), reported_user).as_ref()
+        ],
+        bump = conversation.bump
+    )]
+    pub conversation: Option<Account<'info, Conversation>>,
+
+    // The page holding the reported message, checked against the conversation in the handler
+    pub page: Option<AccountLoader<'info, MessagePage>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(moderator: Pubkey)]
+pub struct AddModerator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ SolDateError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Moderator::INIT_SPACE,
+        seeds = [b"moderator", moderator.as_ref()],
+        bump
+    )]
+    pub moderator_record: Account<'info, Moderator>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(moderator: Pubkey)]
+pub struct RemoveModerator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin @ SolDateError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"moderator", moderator.as_ref()],
+        bump = moderator_record.bump
+    )]
+    pub moderator_record: Account<'info, Moderator>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct ModerateProfile<'info> {
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"moderator", moderator.key().as_ref()],
+        bump = moderator_record.bump
+    )]
+    pub moderator_record: Account<'info, Moderator>,
+
+    #[account(
+        mut,
+        seeds = [b"profile", user.as_ref()],
+        bump = profile.bump
+    )]
+    pub profile: Account<'info, UserProfile>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReport<'info> {
+    pub moderator: Signer<'info>,
+
+    #[account(
+        seeds = [b"moderator", moderator.key().as_ref()],
+        bump = moderator_record.bump
+    )]
+    pub moderator_record: Account<'info, Moderator>,
+
+    #[account(mut)]
+    pub report: Account<'info, Report>,
+}
+
+#[derive(Accounts)]
+#[instruction(reported_user: Pubkey)]
+pub struct CloseReport<'info> {
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    #[account(
+        mut,
+        close = reporter,
+        has_one = reporter @ SolDateError::Unauthorized,
+        constraint = report.status.is_resolved() @ SolDateError::ReportNotResolved,
+        seeds = [b"report", reporter.key().as_ref(), reported_user.as_ref()],
+        bump = report.bump
+    )]
+    pub report: Account<'info, Report>,
+}
+
+#[derive(Accounts)]
+#[instruction(invitee: Pubkey)]
+pub struct ProposeDate<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"profile", proposer.key().as_ref()],
+        bump = proposer_profile.bump
+    )]
+    pub proposer_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"profile", invitee.as_ref()],
+        bump = invitee_profile.bump
+    )]
+    pub invitee_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", proposer.key().as_ref(), invitee.as_ref()],
+        bump
+    )]
+    pub proposer_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", invitee.as_ref(), proposer.key().as_ref()],
+        bump
+    )]
+    pub invitee_block: UncheckedAccount<'info>,
+
+    /// CHECK: Must hold an active `Match`, see `require_active_match`, address is verified by the seeds
+    #[account(
+        seeds = [
+            b"match",
+            std::cmp::min(proposer.key(), invitee).as_ref(),
+            std::cmp::max(proposer.key(), invitee).as_ref()
+        ],
+        bump
+    )]
+    pub match_account: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + DateEscrow::INIT_SPACE,
+        seeds = [
+            b"date",
+            std::cmp::min(proposer.key(), invitee).as_ref(),
+            std::cmp::max(proposer.key(), invitee).as_ref()
+        ],
+        bump
+    )]
+    pub date: Account<'info, DateEscrow>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptDate<'info> {
+    #[account(mut)]
+    pub invitee: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"profile", invitee.key().as_ref()],
+        bump = invitee_profile.bump
+    )]
+    pub invitee_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"profile", date.proposer.as_ref()],
+        bump = proposer_profile.bump
+    )]
+    pub proposer_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", invitee.key().as_ref(), date.proposer.as_ref()],
+        bump
+    )]
+    pub invitee_block: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"block", date.proposer.as_ref(), invitee.key().as_ref()],
+        bump
+    )]
+    pub proposer_block: UncheckedAccount<'info>,
+
+    /// CHECK: Must hold an active `Match`, see `require_active_match`, address is verified by the seeds
+    #[account(
+        seeds = [
+            b"match",
+            std::cmp::min(date.proposer, date.invitee).as_ref(),
+            std::cmp::max(date.proposer, date.invitee).as_ref()
+        ],
+        bump
+    )]
+    pub match_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = invitee @ SolDateError::Unauthorized,
+        constraint = date.status == DateStatus::Proposed @ SolDateError::DateAlreadyAccepted,
+        seeds = [
+            b"date",
+            std::cmp::min(date.proposer, date.invitee).as_ref(),
+            std::cmp::max(date.proposer, date.invitee).as_ref()
+        ],
+        bump = date.bump
+    )]
+    pub date: Account<'info, DateEscrow>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelDate<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer @ SolDateError::InvalidAccount,
+        constraint = date.is_participant(&user.key()) @ SolDateError::Unauthorized,
+        constraint = date.status == DateStatus::Proposed @ SolDateError::DateAlreadyAccepted,
+        seeds = [
+            b"date",
+            std::cmp::min(date.proposer, date.invitee).as_ref(),
+            std::cmp::max(date.proposer, date.invitee).as_ref()
+        ],
+        bump = date.bump
+    )]
+    pub date: Account<'info, DateEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct AttestDate<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = date.is_participant(&user.key()) @ SolDateError::Unauthorized,
+        constraint = date.status == DateStatus::Accepted @ SolDateError::DateNotAccepted,
+        seeds = [
+            b"date",
+            std::cmp::min(date.proposer, date.invitee).as_ref(),
+            std::cmp::max(date.proposer, date.invitee).as_ref()
+        ],
+        bump = date.bump
+    )]
+    pub date: Account<'info, DateEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDate<'info> {
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub invitee: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = proposer @ SolDateError::InvalidAccount,
+        has_one = invitee @ SolDateError::InvalidAccount,
+        constraint = date.status == DateStatus::Accepted @ SolDateError::DateNotAccepted,
+        seeds = [
+            b"date",
+            std::cmp::min(date.proposer, date.invitee).as_ref(),
+            std::cmp::max(date.proposer, date.invitee).as_ref()
+        ],
+        bump = date.bump
+    )]
+    pub date: Account<'info, DateEscrow>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDate<'info> {
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = proposer @ SolDateError::InvalidAccount,
+        constraint = date.is_participant(&user.key()) @ SolDateError::Unauthorized,
+        constraint = date.status == DateStatus::Resolved @ SolDateError::DateNotResolved,
+        seeds = [
+            b"date",
+            std::cmp::min(date.proposer, date.invitee).as_ref(),
+            std::cmp::max(date.proposer, date.invitee).as_ref()
+        ],
+        bump = date.bump
+    )]
+    pub date: Account<'info, DateEscrow>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"review", date.proposer.as_ref(), date.invitee.as_ref()],
+        bump
+    )]
+    pub proposer_review: UncheckedAccount<'info>,
+
+    /// CHECK: Only checked for existence, address is verified by the seeds
+    #[account(
+        seeds = [b"review", date.invitee.as_ref(), date.proposer.as_ref()],
+        bump
+    )]
+    pub invitee_review: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(reviewee: Pubkey)]
+pub struct SubmitReview<'info> {
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"profile", reviewer.key().as_ref()],
+        bump = reviewer_profile.bump
+    )]
+    pub reviewer_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"profile", reviewee.as_ref()],
+        bump = reviewee_profile.bump
+    )]
+    pub reviewee_profile: Account<'info, UserProfile>,
+
+    #[account(
+        init_if_needed,
+        payer = reviewer,
+        space = 8 + UserRecord::INIT_SPACE,
+        seeds = [b"record", reviewee.as_ref()],
+        bump
+    )]
+    pub reviewee_record: Account<'info, UserRecord>,
+
+    // A resolved date record, preferred over the match because it proves the two actually met
+    #[account(
+        seeds = [
+            b"date",
+            std::cmp::min(reviewer.key(), reviewee).as_ref(),
+            std::cmp::max(reviewer.key(), reviewee).as_ref()
+        ],
+        bump = date.bump
+    )]
+    pub date: Option<Account<'info, DateEscrow>>,
+
+    // Only counts while the match is active, checked in the handler
+    #[account(
+        seeds = [
+            b"match",
+            std::cmp::min(reviewer.key(), reviewee).as_ref(),
+            std::cmp::max(reviewer.key(), reviewee).as_ref()
+        ],
+        bump = match_account.bump
+    )]
+    pub match_account: Option<Account<'info, Match>>,
+
+    #[account(
+        init,
+        payer = reviewer,
+        space = 8 + Review::INIT_SPACE,
+        seeds = [b"review", reviewer.key().as_ref(), reviewee.as_ref()],
+        bump
+    )]
+    pub review: Account<'info, Review>,
     pub system_program: Program<'info, System>,
 }
 
+// Hard upper bounds, the account space is allocated for these and the config can only lower them
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_BIO_LEN: usize = 100;
+pub const MAX_INTERESTS: usize = 5;
+pub const MAX_INTEREST_LEN: usize = 16;
+pub const MAX_LOCATION_LEN: usize = 32;
+pub const MIN_AGE_FLOOR: u8 = 18;
+
+// Tip notes only live in the `TipSent` event, so this is not a config limit
+pub const MAX_TIP_NOTE_LEN: usize = 64;
+pub const MAX_DATE_PLACE_LEN: usize = 64;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    // Can pause the program alongside the admin, but not change limits or unpause
+    pub guardian: Pubkey,
+    pub limits: Limits,
+    pub paused: u8,
+    // Lamports `send_super_like` moves into the treasury
+    pub super_like_price: u64,
+    // Default pubkey until the admin sets a premium plan
+    pub premium_mint: Pubkey,
+    pub premium_price: u64,
+    pub premium_period: i64,
+    pub bump: u8
+}
+
+// Program-owned so withdrawals can move lamports directly, fees are sent to it with plain transfers
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub bump: u8
+}
+
+// Deactivating, unliking, unmatching, deleting, blocking, reporting and every closing path stay open
+// while paused, so users can always withdraw themselves and their rent
+impl Config {
+    pub const PAUSE_PROFILES: u8 = 1 << 0;
+    pub const PAUSE_LIKES: u8 = 1 << 1;
+    pub const PAUSE_MESSAGES: u8 = 1 << 2;
+    pub const PAUSE_TIPS: u8 = 1 << 3;
+    pub const PAUSE_DATES: u8 = 1 << 4;
+    pub const PAUSE_PREMIUM: u8 = 1 << 5;
+
+    pub fn require_unpaused(&self, flag: u8) -> Result<()> {
+        require!(self.paused & flag == 0, SolDateError::ProgramPaused);
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Limits {
+    pub max_name_len: u8,
+    pub max_bio_len: u8,
+    pub max_message_len: u8,
+    pub min_age: u8,
+    pub max_interests: u8,
+    pub max_interest_len: u8,
+    pub max_location_len: u8,
+    pub max_matches: u32,
+    pub daily_likes: u16,
+    pub premium_daily_likes: u16,
+}
+
+impl Limits {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_name_len as usize <= MAX_NAME_LEN, SolDateError::InvalidConfig);
+        require!(self.max_bio_len as usize <= MAX_BIO_LEN, SolDateError::InvalidConfig);
+        require!(self.max_message_len as usize <= MAX_MESSAGE_LEN, SolDateError::InvalidConfig);
+        require!(self.min_age >= MIN_AGE_FLOOR, SolDateError::InvalidConfig);
+        require!(self.max_interests as usize <= MAX_INTERESTS, SolDateError::InvalidConfig);
+        require!(self.max_interest_len as usize <= MAX_INTEREST_LEN, SolDateError::InvalidConfig);
+        require!(self.max_location_len as usize <= MAX_LOCATION_LEN, SolDateError::InvalidConfig);
+        require!(self.premium_daily_likes >= self.daily_likes, SolDateError::InvalidConfig);
+        Ok(())
+    }
+
+    pub fn check_name(&self, name: &str) -> Result<()> {
+        require!(name.len() <= self.max_name_len as usize, SolDateError::NameTooLong);
+        Ok(())
+    }
+
+    pub fn check_bio(&self, bio: &str) -> Result<()> {
+        require!(bio.len() <= self.max_bio_len as usize, SolDateError::BioTooLong);
+        Ok(())
+    }
+
+    pub fn check_age(&self, age: u8) -> Result<()> {
+        require!(age >= self.min_age, SolDateError::AgeTooYoung);
+        Ok(())
+    }
+
+    pub fn check_interests(&self, interests: &[String]) -> Result<()> {
+        require!(interests.len() <= self.max_interests as usize, SolDateError::TooManyInterests);
+        for interest in interests {
+            require!(interest.len() <= self.max_interest_len as usize, SolDateError::InterestTooLong);
+        }
+        Ok(())
+    }
+
+    pub fn check_location(&self, location: &str) -> Result<()> {
+        require!(location.len() <= self.max_location_len as usize, SolDateError::LocationTooLong);
+        Ok(())
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct UserProfile {
     pub owner: Pubkey,
-    #[max_len(32)]
+    #[max_len(MAX_NAME_LEN)]
     pub name: String,
     pub age: u8,
-    #[max_len(100)]
+    #[max_len(MAX_BIO_LEN)]
     pub bio: String,
-    #[max_len(5, 16)]
+    #[max_len(MAX_INTERESTS, MAX_INTEREST_LEN)]
     pub interests: Vec<String>,
-    #[max_len(32)]
+    #[max_len(MAX_LOCATION_LEN)]
     pub location: String,
     pub is_active: bool,
+    pub deactivated_at: Option<i64>,
     pub created_at: i64,
-    #[max_len(64)]
-    pub matches: Vec<Pubkey>,
+    pub match_count: u32,
+    pub likes_sent: u32,
+    pub message_pages: u32,
+    // X25519 public key messages to this user are sealed to, all zeroes until published
+    pub encryption_key: [u8; 32],
+    pub encryption_key_version: u16,
+    pub moderation: ModerationStatus,
+    pub moderation_until: Option<i64>,
+    // Day number (unix time / 86400) the `likes_today` counter belongs to
+    pub like_day: u32,
+    pub likes_today: u16,
+    // Premium entitlement expiry, zero if it was never purchased
+    pub premium_until: i64,
+    pub bump: u8
+}
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+// Longest period a single premium purchase can buy
+pub const MAX_PREMIUM_PERIOD: i64 = 366 * SECONDS_PER_DAY;
+
+impl UserProfile {
+    pub fn is_premium(&self, now: i64) -> bool {
+        self.premium_until > now
+    }
+
+    // The counter restarts whenever the day number changes, premium holders get the higher cap
+    pub fn record_daily_like(&mut self, limits: &Limits, now: i64) -> Result<()> {
+        let day = (now / SECONDS_PER_DAY) as u32;
+        if day != self.like_day {
+            self.like_day = day;
+            self.likes_today = 0;
+        }
+
+        let cap = if self.is_premium(now) { limits.premium_daily_likes } else { limits.daily_likes };
+        require!(self.likes_today < cap, SolDateError::DailyLikeLimitReached);
+        self.likes_today += 1;
+        Ok(())
+    }
+
+    // An expired suspension or ban lapses on its own, no moderator has to clear it
+    pub fn require_good_standing(&self) -> Result<()> {
+        if let Some(until) = self.moderation_until {
+            if Clock::get()?.unix_timestamp >= until {
+                return Ok(());
+            }
+        }
+        match self.moderation {
+            ModerationStatus::Clear => Ok(()),
+            ModerationStatus::Suspended => err!(SolDateError::ProfileSuspended),
+            ModerationStatus::Banned => err!(SolDateError::ProfileBanned),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ModerationStatus {
+    Clear,
+    Suspended,
+    Banned,
+}
+
+// Exists only while the admin keeps the key on the moderator list
+#[account]
+#[derive(InitSpace)]
+pub struct Moderator {
+    pub moderator: Pubkey,
+    pub added_by: Pubkey,
+    pub added_at: i64,
     pub bump: u8
 }
 
@@ -297,21 +2844,226 @@ pub struct Like {
     pub receiver: Pubkey,
     pub timestamp: i64,
     pub is_mutual: bool,
+    pub kind: LikeKind,
     pub bump: u8
 }
 
-// Simple message account with String (reduced size)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum LikeKind {
+    Normal,
+    Super,
+}
+
+// One account per matched pair, seeded by the sorted owner keys
 #[account]
 #[derive(InitSpace)]
-pub struct MessageAccount {
-    pub sender: Pubkey,
-    pub receiver: Pubkey,
-    #[max_len(80)] // Reduced from 200 to 80 for better memory management
-    pub content: String,
-    pub timestamp: i64,
+pub struct Match {
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub initiator: Pubkey,
+    // Paid the rent when the record was first created, a reused record keeps its payer
+    pub payer: Pubkey,
+    pub matched_at: i64,
+    pub status: MatchStatus,
+    pub bump: u8
+}
+
+impl Match {
+    pub fn pair(a: Pubkey, b: Pubkey) -> (Pubkey, Pubkey) {
+        if a < b { (a, b) } else { (b, a) }
+    }
+
+    pub fn other(&self, user: &Pubkey) -> Option<Pubkey> {
+        if *user == self.user_a {
+            Some(self.user_b)
+        } else if *user == self.user_b {
+            Some(self.user_a)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MatchStatus {
+    Active,
+    Unmatched,
+}
+
+// One account per pair of users, its sequence counter picks the message page a new message goes to
+#[account]
+#[derive(InitSpace)]
+pub struct Conversation {
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    // Sent the first message and paid the rent, gets it back through `close_conversation`
+    pub payer: Pubkey,
+    pub next_seq: u64,
+    pub last_message_at: i64,
+    pub sent_by_a: u64,
+    pub sent_by_b: u64,
+    // Messages with a `seq` below the cursor have been read by that participant
+    pub read_cursor_a: u64,
+    pub read_cursor_b: u64,
+    // Message pages that have not been closed yet, the conversation can only be closed at zero
+    pub open_pages: u32,
+    // Whether `user_a` and `user_b` used their intro message, survives unliking and unmatching
+    pub intro_sent: [bool; 2],
     pub bump: u8
 }
 
+impl Conversation {
+    // Assigns the next sequence number to a message, initializing the pair on first use
+    pub fn record_message(&mut self, sender: Pubkey, receiver: Pubkey, timestamp: i64, bump: u8) -> Result<u64> {
+        if self.user_a == Pubkey::default() {
+            let (user_a, user_b) = Match::pair(sender, receiver);
+            self.user_a = user_a;
+            self.user_b = user_b;
+            self.payer = sender;
+            self.bump = bump;
+        }
+
+        match self.participant_index(&sender)? {
+            0 => self.sent_by_a += 1,
+            _ => self.sent_by_b += 1,
+        }
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.last_message_at = timestamp;
+        Ok(seq)
+    }
+
+    pub fn participant_index(&self, user: &Pubkey) -> Result<u8> {
+        if *user == self.user_a {
+            Ok(0)
+        } else if *user == self.user_b {
+            Ok(1)
+        } else {
+            err!(SolDateError::InvalidUser)
+        }
+    }
+}
+
+pub const MAX_MESSAGE_LEN: usize = 80;
+pub const MESSAGES_PER_PAGE: usize = 32;
+pub const MESSAGE_FORMAT_VERSION: u8 = 1;
+pub const NONCE_LEN: usize = 24;
+pub const TAG_LEN: usize = 16;
+pub const MAX_CIPHERTEXT_LEN: usize = MAX_MESSAGE_LEN + TAG_LEN;
+
+// NaCl box between the sender's and receiver's X25519 keys, see `crypto`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EncryptedPayload {
+    pub version: u8,
+    // The receiver's `encryption_key_version` the payload was sealed to
+    pub key_version: u16,
+    pub nonce: [u8; NONCE_LEN],
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedPayload {
+    pub fn validate(&self, limits: &Limits, sender_profile: &UserProfile, receiver_profile: &UserProfile) -> Result<()> {
+        require!(self.version == MESSAGE_FORMAT_VERSION, SolDateError::UnsupportedMessageVersion);
+        require!(self.ciphertext.len() <= limits.max_message_len as usize + TAG_LEN, SolDateError::MessageTooLong);
+        require!(self.ciphertext.len() >= TAG_LEN, SolDateError::InvalidCiphertext);
+        require!(sender_profile.encryption_key_version > 0, SolDateError::EncryptionKeyMissing);
+        require!(receiver_profile.encryption_key_version > 0, SolDateError::EncryptionKeyMissing);
+        require!(self.key_version == receiver_profile.encryption_key_version, SolDateError::StaleEncryptionKey);
+        Ok(())
+    }
+}
+
+// Fixed-size page of a conversation log, message `seq` lives at `seq % MESSAGES_PER_PAGE`
+// of page `seq / MESSAGES_PER_PAGE`
+#[account(zero_copy)]
+pub struct MessagePage {
+    pub conversation: Pubkey,
+    pub payer: Pubkey,
+    pub page_index: u64,
+    // Lamports every message holds in the page until it is deleted or the page is closed
+    pub slot_deposit: u64,
+    // Deposits currently held for `user_a` and `user_b`
+    pub deposits: [u64; 2],
+    // Messages written since the page was opened, a page that `close_profile` closed early starts over
+    pub count: u32,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub entries: [MessageEntry; MESSAGES_PER_PAGE],
+}
+
+impl MessagePage {
+    pub fn index_for(seq: u64) -> u64 {
+        seq / MESSAGES_PER_PAGE as u64
+    }
+
+    // First sequence number past the page, the page is full once the conversation gets there
+    pub fn end_seq(page_index: u64) -> u64 {
+        (page_index + 1) * MESSAGES_PER_PAGE as u64
+    }
+
+    pub fn entry(&self, seq: u64) -> &MessageEntry {
+        &self.entries[(seq % MESSAGES_PER_PAGE as u64) as usize]
+    }
+
+    pub fn entry_mut(&mut self, seq: u64) -> &mut MessageEntry {
+        &mut self.entries[(seq % MESSAGES_PER_PAGE as u64) as usize]
+    }
+
+    pub fn write(&mut self, seq: u64, sender_index: u8, timestamp: i64, sender_key_version: u16, payload: &EncryptedPayload) {
+        let entry = self.entry_mut(seq);
+        entry.timestamp = timestamp;
+        entry.sender_index = sender_index;
+        entry.set_payload(payload, sender_key_version);
+        self.count += 1;
+    }
+}
+
+#[zero_copy]
+pub struct MessageEntry {
+    pub timestamp: i64,
+    pub nonce: [u8; NONCE_LEN],
+    // Receiver and sender key versions the ciphertext was sealed with
+    pub key_version: u16,
+    pub sender_key_version: u16,
+    // 0 for the conversation's `user_a`, 1 for `user_b`
+    pub sender_index: u8,
+    pub version: u8,
+    pub len: u8,
+    pub flags: u8,
+    pub ciphertext: [u8; MAX_CIPHERTEXT_LEN],
+    // 0 until the sender edits the message
+    pub edited_at: i64,
+}
+
+impl MessageEntry {
+    pub const DELETED: u8 = 1 << 0;
+    pub const HIDDEN_BY_RECEIVER: u8 = 1 << 1;
+
+    // Slots of a page that was closed early and opened again read back empty
+    pub fn is_deleted(&self) -> bool {
+        self.flags & Self::DELETED != 0 || self.timestamp == 0
+    }
+
+    pub fn set_payload(&mut self, payload: &EncryptedPayload, sender_key_version: u16) {
+        self.nonce = payload.nonce;
+        self.key_version = payload.key_version;
+        self.sender_key_version = sender_key_version;
+        self.version = payload.version;
+        self.len = payload.ciphertext.len() as u8;
+        self.ciphertext = [0; MAX_CIPHERTEXT_LEN];
+        self.ciphertext[..payload.ciphertext.len()].copy_from_slice(&payload.ciphertext);
+    }
+
+    // Keeps the slot and its metadata so sequence numbers stay stable, only the content is wiped
+    pub fn delete(&mut self) {
+        self.nonce = [0; NONCE_LEN];
+        self.ciphertext = [0; MAX_CIPHERTEXT_LEN];
+        self.len = 0;
+        self.flags |= Self::DELETED;
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct BlockedUser {
@@ -321,6 +3073,496 @@ pub struct BlockedUser {
     pub bump: u8
 }
 
+// Per-wallet history that outlives the profile, `close_profile` never touches it
+#[account]
+#[derive(InitSpace)]
+pub struct UserRecord {
+    pub owner: Pubkey,
+    // Reports filed against this user, never decremented
+    pub report_count: u32,
+    // Only written by `submit_review`, so a trust score derived from it cannot be inflated off-chain
+    pub reputation: Reputation,
+    pub bump: u8
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct Reputation {
+    pub reviews: u32,
+    // Reviews backed by an attended date rather than just a match
+    pub verified_reviews: u32,
+    pub respectful: u32,
+    pub as_pictured: u32,
+    pub would_meet_again: u32,
+}
+
+impl Reputation {
+    pub fn record(&mut self, rating: &Rating, verified: bool) {
+        self.reviews += 1;
+        self.verified_reviews += verified as u32;
+        self.respectful += rating.respectful as u32;
+        self.as_pictured += rating.as_pictured as u32;
+        self.would_meet_again += rating.would_meet_again as u32;
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Report {
+    pub reporter: Pubkey,
+    pub reported: Pubkey,
+    pub reason: ReportReason,
+    pub message: Option<MessageRef>,
+    pub status: ReportStatus,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct MessageRef {
+    pub conversation: Pubkey,
+    pub seq: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ReportReason {
+    Harassment,
+    Spam,
+    FakeProfile,
+    InappropriateContent,
+    Underage,
+    Other,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ReportStatus {
+    Open,
+    UnderReview,
+    ActionTaken,
+    Dismissed,
+}
+
+impl ReportStatus {
+    pub fn is_resolved(&self) -> bool {
+        matches!(self, ReportStatus::ActionTaken | ReportStatus::Dismissed)
+    }
+}
+
+// One date at a time per matched pair, holding both deposits until it is resolved
+#[account]
+#[derive(InitSpace)]
+pub struct DateEscrow {
+    pub proposer: Pubkey,
+    pub invitee: Pubkey,
+    pub date_time: i64,
+    #[max_len(MAX_DATE_PLACE_LEN)]
+    pub place: String,
+    // Lamports each side puts in
+    pub deposit: u64,
+    pub status: DateStatus,
+    // Whether the proposer says the invitee showed up, and the other way around
+    pub proposer_attestation: Option<bool>,
+    pub invitee_attestation: Option<bool>,
+    pub outcome: Option<DateOutcome>,
+    pub created_at: i64,
+    pub bump: u8
+}
+
+// How long after the date time attestations are waited for before the date can be settled without them
+pub const DATE_ATTESTATION_WINDOW: i64 = 3 * SECONDS_PER_DAY;
+// How long after the date time an attended date is kept for reviews unless both were already submitted
+pub const DATE_REVIEW_WINDOW: i64 = 14 * SECONDS_PER_DAY;
+
+impl DateEscrow {
+    pub fn is_participant(&self, user: &Pubkey) -> bool {
+        *user == self.proposer || *user == self.invitee
+    }
+
+    // A missing attestation never counts against the other side, and two no-show claims cancel out
+    pub fn settle(&self) -> DateOutcome {
+        match (self.proposer_attestation, self.invitee_attestation) {
+            (Some(false), Some(false)) => DateOutcome::Disputed,
+            (Some(false), _) => DateOutcome::InviteeNoShow,
+            (_, Some(false)) => DateOutcome::ProposerNoShow,
+            (None, None) => DateOutcome::Unattested,
+            _ => DateOutcome::Attended,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DateStatus {
+    Proposed,
+    Accepted,
+    Resolved,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DateOutcome {
+    Attended,
+    ProposerNoShow,
+    InviteeNoShow,
+    Disputed,
+    Unattested,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Review {
+    pub reviewer: Pubkey,
+    pub reviewee: Pubkey,
+    pub rating: Rating,
+    // The attended date backing the review, None if it rests on a match
+    pub date: Option<Pubkey>,
+    pub created_at: i64,
+    pub bump: u8
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Rating {
+    pub respectful: bool,
+    pub as_pictured: bool,
+    pub would_meet_again: bool,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub updated_by: Pubkey,
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub limits: Limits,
+    pub super_like_price: u64,
+    pub premium_mint: Pubkey,
+    pub premium_price: u64,
+    pub premium_period: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub authority: Pubkey,
+    pub paused: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub admin: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PremiumPurchased {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub premium_until: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProfileCreated {
+    pub owner: Pubkey,
+    pub profile: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProfileUpdated {
+    pub owner: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProfileDeactivated {
+    pub owner: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProfileReactivated {
+    pub owner: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProfileClosed {
+    pub owner: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+// The profile only holds the latest key, clients rebuild older versions from these events
+#[event]
+pub struct EncryptionKeyPublished {
+    pub owner: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub key_version: u16,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct LikeSent {
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub is_mutual: bool,
+    pub kind: LikeKind,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct LikeRemoved {
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MatchCreated {
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub initiator: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MatchEnded {
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub ended_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MatchClosed {
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub closed_by: Pubkey,
+    pub payer: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+// Only metadata is emitted, the ciphertext stays in the conversation page
+#[event]
+pub struct MessageSent {
+    pub conversation: Pubkey,
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub seq: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MessageEdited {
+    pub conversation: Pubkey,
+    pub sender: Pubkey,
+    pub seq: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+// `by_sender` is false when the receiver only hid the message from their side
+#[event]
+pub struct MessageRemoved {
+    pub conversation: Pubkey,
+    pub user: Pubkey,
+    pub seq: u64,
+    pub by_sender: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MessagesRead {
+    pub conversation: Pubkey,
+    pub reader: Pubkey,
+    pub last_read_seq: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MessagePageClosed {
+    pub conversation: Pubkey,
+    pub payer: Pubkey,
+    pub page_index: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ConversationClosed {
+    pub conversation: Pubkey,
+    pub closed_by: Pubkey,
+    pub payer: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct TipSent {
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    // None for a SOL tip
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub note: Option<String>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct UserBlocked {
+    pub blocker: Pubkey,
+    pub blocked: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ReportFiled {
+    pub report: Pubkey,
+    pub reporter: Pubkey,
+    pub reported: Pubkey,
+    pub reason: ReportReason,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ReportStatusUpdated {
+    pub report: Pubkey,
+    pub moderator: Pubkey,
+    pub status: ReportStatus,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ReportClosed {
+    pub report: Pubkey,
+    pub reporter: Pubkey,
+    pub reported: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ModeratorAdded {
+    pub moderator: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ModeratorRemoved {
+    pub moderator: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProfileModerated {
+    pub user: Pubkey,
+    pub moderator: Pubkey,
+    pub status: ModerationStatus,
+    pub until: Option<i64>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DateProposed {
+    pub date: Pubkey,
+    pub proposer: Pubkey,
+    pub invitee: Pubkey,
+    pub date_time: i64,
+    pub deposit: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DateAccepted {
+    pub date: Pubkey,
+    pub proposer: Pubkey,
+    pub invitee: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DateCancelled {
+    pub date: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DateAttested {
+    pub date: Pubkey,
+    pub user: Pubkey,
+    pub other_showed_up: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DateResolved {
+    pub date: Pubkey,
+    pub outcome: DateOutcome,
+    pub proposer_payout: u64,
+    pub invitee_payout: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DateClosed {
+    pub date: Pubkey,
+    pub closed_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ReviewSubmitted {
+    pub reviewer: Pubkey,
+    pub reviewee: Pubkey,
+    pub rating: Rating,
+    pub verified_date: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct UserUnblocked {
+    pub blocker: Pubkey,
+    pub blocked: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
 #[error_code]
 pub enum SolDateError {
     #[msg("Unauthorized access")]
@@ -347,4 +3589,141 @@ pub enum SolDateError {
     NotMutualLikes,
     #[msg("No like exists between users")]
     NoLikeExists,
-}
\ No newline at end of file
+    #[msg("User is blocked")]
+    UserBlocked,
+    #[msg("Invalid account")]
+    InvalidAccount,
+    #[msg("Profile still has outstanding likes, messages or matches")]
+    OutstandingAccounts,
+    #[msg("Profile is already active")]
+    ProfileAlreadyActive,
+    #[msg("Reverse like account is required")]
+    ReverseLikeRequired,
+    #[msg("Match account is required")]
+    MatchAccountRequired,
+    #[msg("Intro message already sent to this user")]
+    IntroAlreadySent,
+    #[msg("Message page is not full yet")]
+    PageNotFull,
+    #[msg("Unsupported message format version")]
+    UnsupportedMessageVersion,
+    #[msg("Ciphertext is shorter than its authentication tag")]
+    InvalidCiphertext,
+    #[msg("Encryption key has not been published")]
+    EncryptionKeyMissing,
+    #[msg("Message was sealed to an outdated encryption key")]
+    StaleEncryptionKey,
+    #[msg("Message sequence does not exist")]
+    InvalidSequence,
+    #[msg("Messages up to this sequence were already read")]
+    AlreadyRead,
+    #[msg("Message has been deleted")]
+    MessageDeleted,
+    #[msg("Config limit exceeds the allocated maximum")]
+    InvalidConfig,
+    #[msg("Too many interests")]
+    TooManyInterests,
+    #[msg("Interest is too long")]
+    InterestTooLong,
+    #[msg("Location is too long")]
+    LocationTooLong,
+    #[msg("Match limit reached")]
+    MatchLimitReached,
+    #[msg("This part of the program is paused")]
+    ProgramPaused,
+    #[msg("Cannot report self")]
+    CannotReportSelf,
+    #[msg("Report has not been resolved yet")]
+    ReportNotResolved,
+    #[msg("Profile is suspended")]
+    ProfileSuspended,
+    #[msg("Profile is banned")]
+    ProfileBanned,
+    #[msg("Moderation expiry must be in the future")]
+    InvalidModerationExpiry,
+    #[msg("Daily like limit reached, come back tomorrow")]
+    DailyLikeLimitReached,
+    #[msg("Treasury balance is too low")]
+    InsufficientTreasuryBalance,
+    #[msg("Mint is not accepted for premium")]
+    UnsupportedPremiumMint,
+    #[msg("Tip amount must be greater than zero")]
+    InvalidTipAmount,
+    #[msg("Tip note is too long")]
+    TipNoteTooLong,
+    #[msg("Place is too long")]
+    PlaceTooLong,
+    #[msg("Deposit must be greater than zero")]
+    InvalidDeposit,
+    #[msg("Date time must be in the future")]
+    InvalidDateTime,
+    #[msg("Date was already accepted")]
+    DateAlreadyAccepted,
+    #[msg("Date has not been accepted")]
+    DateNotAccepted,
+    #[msg("Date has not started yet")]
+    DateNotStarted,
+    #[msg("Already attested for this date")]
+    AlreadyAttested,
+    #[msg("Date cannot be resolved yet")]
+    DateNotResolvable,
+    #[msg("Date has not been resolved yet")]
+    DateNotResolved,
+    #[msg("Reviews need an attended date or a match between the two users")]
+    NoDateOrMatch,
+    #[msg("Match is still active")]
+    MatchStillActive,
+    #[msg("Conversation still has open message pages")]
+    ConversationHasPages,
+    #[msg("Both participants have to read a page before it is closed")]
+    PageUnread,
+    #[msg("Message was not sent by the reported user")]
+    MessageNotFromReported,
+    #[msg("Premium expiry is out of range")]
+    PremiumOverflow,
+    #[msg("Date is kept until both reviews are in or the review window has passed")]
+    ReviewWindowOpen,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(proposer_attestation: Option<bool>, invitee_attestation: Option<bool>) -> DateEscrow {
+        DateEscrow {
+            proposer: Pubkey::new_unique(),
+            invitee: Pubkey::new_unique(),
+            date_time: 0,
+            place: String::new(),
+            deposit: 1,
+            status: DateStatus::Accepted,
+            proposer_attestation,
+            invitee_attestation,
+            outcome: None,
+            created_at: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn settle_covers_every_attestation_pair() {
+        let cases = [
+            (None, None, DateOutcome::Unattested),
+            (None, Some(true), DateOutcome::Attended),
+            (None, Some(false), DateOutcome::ProposerNoShow),
+            (Some(true), None, DateOutcome::Attended),
+            (Some(true), Some(true), DateOutcome::Attended),
+            (Some(true), Some(false), DateOutcome::ProposerNoShow),
+            (Some(false), None, DateOutcome::InviteeNoShow),
+            (Some(false), Some(true), DateOutcome::InviteeNoShow),
+            (Some(false), Some(false), DateOutcome::Disputed),
+        ];
+
+        for (proposer_attestation, invitee_attestation, expected) in cases {
+            assert!(
+                date(proposer_attestation, invitee_attestation).settle() == expected,
+                "proposer {proposer_attestation:?}, invitee {invitee_attestation:?}"
+            );
+        }
+    }
+}
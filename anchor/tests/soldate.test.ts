@@ -1,6 +1,16 @@
 import * as anchor from '@coral-xyz/anchor'
 import { Program } from '@coral-xyz/anchor'
 import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js'
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccount,
+  createMint,
+  getAssociatedTokenAddressSync,
+  getAccount,
+  mintTo,
+} from '@solana/spl-token'
+import nacl from 'tweetnacl'
 import { Soldate } from '../target/types/soldate'
 
 describe('Soldate', () => {
@@ -13,6 +23,130 @@ describe('Soldate', () => {
   const user1 = Keypair.generate();
   const user2 = Keypair.generate();
   const user3 = Keypair.generate();
+  const user4 = Keypair.generate();
+
+  // X25519 keys published with `setEncryptionKey`, payloads use the same box as `crypto.rs`
+  const encryptionKeys = new Map([user1, user2, user3, user4].map((user) => [user.publicKey.toBase58(), nacl.box.keyPair()]));
+  const encryptionKey = (user: PublicKey) => encryptionKeys.get(user.toBase58())!;
+  const MESSAGE_FORMAT_VERSION = 1;
+
+  const findConfigPda = () =>
+    PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId)[0];
+
+  const findTreasuryPda = () =>
+    PublicKey.findProgramAddressSync([Buffer.from("treasury")], program.programId)[0];
+
+  const findModeratorPda = (moderator: PublicKey) =>
+    PublicKey.findProgramAddressSync([Buffer.from("moderator"), moderator.toBuffer()], program.programId)[0];
+
+  const findRecordPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync([Buffer.from("record"), user.toBuffer()], program.programId)[0];
+
+  const findReviewPda = (reviewer: PublicKey, reviewee: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("review"), reviewer.toBuffer(), reviewee.toBuffer()],
+      program.programId
+    )[0];
+
+  const DEFAULT_LIMITS = {
+    maxNameLen: 32,
+    maxBioLen: 100,
+    maxMessageLen: 80,
+    minAge: 18,
+    maxInterests: 5,
+    maxInterestLen: 16,
+    maxLocationLen: 32,
+    maxMatches: 64,
+    dailyLikes: 20,
+    premiumDailyLikes: 100,
+  };
+
+  const findProfilePda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const findLikePda = (sender: PublicKey, receiver: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("like"), sender.toBuffer(), receiver.toBuffer()],
+      program.programId
+    )[0];
+
+  const findMatchPda = (a: PublicKey, b: PublicKey) => {
+    const [first, second] = Buffer.compare(a.toBuffer(), b.toBuffer()) < 0 ? [a, b] : [b, a];
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("match"), first.toBuffer(), second.toBuffer()],
+      program.programId
+    )[0];
+  };
+
+  const findConversationPda = (a: PublicKey, b: PublicKey) => {
+    const [first, second] = Buffer.compare(a.toBuffer(), b.toBuffer()) < 0 ? [a, b] : [b, a];
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("conversation"), first.toBuffer(), second.toBuffer()],
+      program.programId
+    )[0];
+  };
+
+  const MESSAGES_PER_PAGE = 32;
+
+  // Message `seq` is stored in page `seq / MESSAGES_PER_PAGE` of the conversation log
+  const findPagePda = (conversation: PublicKey, pageIndex: number) => {
+    const index = Buffer.alloc(8);
+    index.writeBigUInt64LE(BigInt(pageIndex), 0);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("page"), conversation.toBuffer(), index],
+      program.programId
+    )[0];
+  };
+
+  const nextPagePda = async (conversation: PublicKey) => {
+    const account = await program.account.conversation.fetchNullable(conversation);
+    const nextSeq = account ? account.nextSeq.toNumber() : 0;
+    return findPagePda(conversation, Math.floor(nextSeq / MESSAGES_PER_PAGE));
+  };
+
+  const sealMessage = (sender: PublicKey, receiver: PublicKey, text: string, keyVersion = 1) => {
+    const nonce = nacl.randomBytes(nacl.box.nonceLength);
+    const ciphertext = nacl.box(
+      Buffer.from(text),
+      nonce,
+      encryptionKey(receiver).publicKey,
+      encryptionKey(sender).secretKey
+    );
+    return { version: MESSAGE_FORMAT_VERSION, keyVersion, nonce: [...nonce], ciphertext: Buffer.from(ciphertext) };
+  };
+
+  // Opened by the receiver, the sender could equally open it with the receiver's public key
+  const decodeEntry = (
+    entry: { len: number; nonce: number[]; ciphertext: number[] },
+    sender: PublicKey,
+    receiver: PublicKey
+  ) => {
+    const plaintext = nacl.box.open(
+      Uint8Array.from(entry.ciphertext.slice(0, entry.len)),
+      Uint8Array.from(entry.nonce),
+      encryptionKey(sender).publicKey,
+      encryptionKey(receiver).secretKey
+    );
+    return plaintext ? Buffer.from(plaintext).toString() : null;
+  };
+
+  const findBlockPda = (blocker: PublicKey, blocked: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("block"), blocker.toBuffer(), blocked.toBuffer()],
+      program.programId
+    )[0];
+
+  const emittedEvents = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+  };
 
   beforeAll(async() => {
     const tx1 = await provider.connection.requestAirdrop(user1.publicKey, 2 * LAMPORTS_PER_SOL);
@@ -23,6 +157,48 @@ describe('Soldate', () => {
 
     const tx3 = await provider.connection.requestAirdrop(user3.publicKey, 2 * LAMPORTS_PER_SOL);
     await provider.connection.confirmTransaction(tx3, "confirmed");
+
+    const tx4 = await provider.connection.requestAirdrop(user4.publicKey, 2 * LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(tx4, "confirmed");
+  })
+
+  it('Initialize config', async () => {
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    // Only the upgrade authority, the provider wallet on localnet, may create the config
+    await expect(
+      program.methods
+        .initialize(DEFAULT_LIMITS)
+        .accountsStrict({
+          authority: user1.publicKey,
+          config: findConfigPda(),
+          treasury: findTreasuryPda(),
+          program: program.programId,
+          programData,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/Unauthorized/);
+
+    await program.methods
+      .initialize(DEFAULT_LIMITS)
+      .accountsStrict({
+        authority: provider.wallet.publicKey,
+        config: findConfigPda(),
+        treasury: findTreasuryPda(),
+        program: program.programId,
+        programData,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    const config = await program.account.config.fetch(findConfigPda());
+    expect(config.admin.equals(provider.wallet.publicKey)).toBe(true);
+    expect(config.limits.maxMatches).toBe(64);
   })
 
   it('Initialize user1 profile', async () => {
@@ -40,6 +216,7 @@ describe('Soldate', () => {
     await program.methods
       .createProfile(NAME, AGE, BIO, INTERESTS, LOCATION)
       .accountsStrict({
+        config: findConfigPda(),
         user: user1.publicKey,
         profile: profilePDA,
         systemProgram: SystemProgram.programId,
@@ -66,6 +243,7 @@ describe('Soldate', () => {
     await program.methods
       .createProfile(NAME, AGE, BIO, INTERESTS, LOCATION)
       .accountsStrict({
+        config: findConfigPda(),
         user: user2.publicKey,
         profile: profilePDA,
         systemProgram: SystemProgram.programId,
@@ -92,6 +270,7 @@ describe('Soldate', () => {
     await program.methods
       .createProfile(NAME, AGE, BIO, INTERESTS, LOCATION)
       .accountsStrict({
+        config: findConfigPda(),
         user: user3.publicKey,
         profile: profilePDA,
         systemProgram: SystemProgram.programId,
@@ -103,6 +282,19 @@ describe('Soldate', () => {
     console.log("user3 profile: ", profile);
   })
 
+  it('Initialize user4 profile', async () => {
+    await program.methods
+      .createProfile("FOURTH", 24, "FOURTH_USER_BIO", ["Good", "Kind"], "Gotham")
+      .accountsStrict({
+        config: findConfigPda(),
+        user: user4.publicKey,
+        profile: findProfilePda(user4.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user4])
+      .rpc()
+  })
+
   it('update user1 profile', async () => {
     const [profilePDA] = PublicKey.findProgramAddressSync(
       [Buffer.from("profile"), user1.publicKey.toBuffer()],
@@ -118,6 +310,7 @@ describe('Soldate', () => {
         "Gotham"
       )
       .accountsStrict({
+        config: findConfigPda(),
         user: user1.publicKey,
         profile: profilePDA,
         systemProgram: SystemProgram.programId,
@@ -129,6 +322,426 @@ describe('Soldate', () => {
     console.log("user1 profile updated: ", profile);
   })
 
+  it('admin tightens config limits', async () => {
+    await expect(
+      program.methods
+        .updateConfig({ ...DEFAULT_LIMITS, maxNameLen: 33 }, null)
+        .accountsStrict({
+          admin: provider.wallet.publicKey,
+          config: findConfigPda(),
+        })
+        .rpc()
+    ).rejects.toThrow(/InvalidConfig/);
+
+    const signature = await program.methods
+      .updateConfig({ ...DEFAULT_LIMITS, maxNameLen: 8 }, null)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc({ commitment: "confirmed" })
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("configUpdated");
+    expect(event.data.limits.maxNameLen).toBe(8);
+    expect(event.data.updatedBy.toBase58()).toBe(provider.wallet.publicKey.toBase58());
+
+    await expect(
+      program.methods
+        .updateProfile("FIRST_USER_GOAT", null, null, null, null)
+        .accountsStrict({
+          user: user1.publicKey,
+          config: findConfigPda(),
+          profile: findProfilePda(user1.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/NameTooLong/);
+
+    await program.methods
+      .updateConfig(DEFAULT_LIMITS, null)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+  })
+
+  it('guardian pauses likes until the admin lifts it', async () => {
+    const guardian = Keypair.generate();
+    const PAUSE_LIKES = 1 << 1;
+
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+
+    await program.methods
+      .setPaused(PAUSE_LIKES)
+      .accountsStrict({
+        authority: guardian.publicKey,
+        config: findConfigPda(),
+      })
+      .signers([guardian])
+      .rpc()
+
+    await expect(
+      program.methods
+        .sendLike(user2.publicKey)
+        .accountsStrict({
+          sender: user1.publicKey,
+          config: findConfigPda(),
+          senderProfile: findProfilePda(user1.publicKey),
+          targetProfile: findProfilePda(user2.publicKey),
+          like: findLikePda(user1.publicKey, user2.publicKey),
+          reverseLike: findLikePda(user2.publicKey, user1.publicKey),
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          targetBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          matchAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/ProgramPaused/);
+
+    await expect(
+      program.methods
+        .setPaused(0)
+        .accountsStrict({
+          authority: guardian.publicKey,
+          config: findConfigPda(),
+        })
+        .signers([guardian])
+        .rpc()
+    ).rejects.toThrow(/Unauthorized/);
+
+    await program.methods
+      .setPaused(0)
+      .accountsStrict({
+        authority: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+
+    const config = await program.account.config.fetch(findConfigPda());
+    expect(config.paused).toBe(0);
+  })
+
+  it('moderator suspends user1 until the suspension is cleared', async () => {
+    await expect(
+      program.methods
+        .addModerator(user3.publicKey)
+        .accountsStrict({
+          admin: user3.publicKey,
+          config: findConfigPda(),
+          moderatorRecord: findModeratorPda(user3.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/Unauthorized/);
+
+    const signature = await program.methods
+      .addModerator(provider.wallet.publicKey)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+        moderatorRecord: findModeratorPda(provider.wallet.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" })
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("moderatorAdded");
+    expect(event.data.moderator.toBase58()).toBe(provider.wallet.publicKey.toBase58());
+
+    const moderateAccounts = {
+      moderator: provider.wallet.publicKey,
+      moderatorRecord: findModeratorPda(provider.wallet.publicKey),
+      profile: findProfilePda(user1.publicKey),
+    };
+
+    await program.methods
+      .suspendProfile(user1.publicKey, null)
+      .accountsStrict(moderateAccounts)
+      .rpc()
+
+    let profile = await program.account.userProfile.fetch(findProfilePda(user1.publicKey));
+    expect(profile.moderation).toEqual({ suspended: {} });
+
+    await expect(
+      program.methods
+        .sendLike(user2.publicKey)
+        .accountsStrict({
+          sender: user1.publicKey,
+          config: findConfigPda(),
+          senderProfile: findProfilePda(user1.publicKey),
+          targetProfile: findProfilePda(user2.publicKey),
+          like: findLikePda(user1.publicKey, user2.publicKey),
+          reverseLike: findLikePda(user2.publicKey, user1.publicKey),
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          targetBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          matchAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/ProfileSuspended/);
+
+    await expect(
+      program.methods
+        .updateProfile(null, null, "Suspended bio", null, null)
+        .accountsStrict({
+          config: findConfigPda(),
+          user: user1.publicKey,
+          profile: findProfilePda(user1.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/ProfileSuspended/);
+
+    // Closing the profile would reset the moderation state along with it
+    await expect(
+      program.methods
+        .closeProfile()
+        .accountsStrict({
+          user: user1.publicKey,
+          profile: findProfilePda(user1.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/ProfileSuspended/);
+
+    await program.methods
+      .clearModeration(user1.publicKey)
+      .accountsStrict(moderateAccounts)
+      .rpc()
+
+    profile = await program.account.userProfile.fetch(findProfilePda(user1.publicKey));
+    expect(profile.moderation).toEqual({ clear: {} });
+    expect(profile.moderationUntil).toBeNull();
+  })
+
+  it('daily like quota rejects likes over the cap', async () => {
+    await expect(
+      program.methods
+        .updateConfig({ ...DEFAULT_LIMITS, dailyLikes: 200 }, null)
+        .accountsStrict({
+          admin: provider.wallet.publicKey,
+          config: findConfigPda(),
+        })
+        .rpc()
+    ).rejects.toThrow(/InvalidConfig/);
+
+    await program.methods
+      .updateConfig({ ...DEFAULT_LIMITS, dailyLikes: 0, premiumDailyLikes: 0 }, null)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+
+    await expect(
+      program.methods
+        .sendLike(user1.publicKey)
+        .accountsStrict({
+          sender: user3.publicKey,
+          config: findConfigPda(),
+          senderProfile: findProfilePda(user3.publicKey),
+          targetProfile: findProfilePda(user1.publicKey),
+          like: findLikePda(user3.publicKey, user1.publicKey),
+          reverseLike: findLikePda(user1.publicKey, user3.publicKey),
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          targetBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          matchAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/DailyLikeLimitReached/);
+
+    await program.methods
+      .updateConfig(DEFAULT_LIMITS, null)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+  })
+
+  it('premium purchase extends the remaining time', async () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const PREMIUM_PERIOD = 30 * 24 * 60 * 60;
+    const mint = await createMint(provider.connection, payer, payer.publicKey, null, 0);
+    const buyerTokenAccount = await createAssociatedTokenAccount(provider.connection, payer, mint, user2.publicKey);
+    await mintTo(provider.connection, payer, mint, buyerTokenAccount, payer, 100);
+
+    await expect(
+      program.methods
+        .setPremiumPlan(mint, new anchor.BN(10), new anchor.BN(400 * 24 * 60 * 60))
+        .accountsStrict({
+          admin: provider.wallet.publicKey,
+          config: findConfigPda(),
+        })
+        .rpc()
+    ).rejects.toThrow(/InvalidConfig/);
+
+    await expect(
+      program.methods
+        .setPremiumPlan(mint, new anchor.BN(0), new anchor.BN(PREMIUM_PERIOD))
+        .accountsStrict({
+          admin: provider.wallet.publicKey,
+          config: findConfigPda(),
+        })
+        .rpc()
+    ).rejects.toThrow(/InvalidConfig/);
+
+    await program.methods
+      .setPremiumPlan(mint, new anchor.BN(10), new anchor.BN(PREMIUM_PERIOD))
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+
+    const treasuryTokenAccount = getAssociatedTokenAddressSync(mint, findTreasuryPda(), true);
+    const purchaseAccounts = {
+      buyer: user2.publicKey,
+      config: findConfigPda(),
+      profile: findProfilePda(user2.publicKey),
+      mint,
+      buyerTokenAccount,
+      treasury: findTreasuryPda(),
+      treasuryTokenAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    const otherMint = await createMint(provider.connection, payer, payer.publicKey, null, 0);
+    await expect(
+      program.methods
+        .purchasePremium()
+        .accountsStrict({
+          ...purchaseAccounts,
+          mint: otherMint,
+          buyerTokenAccount: await createAssociatedTokenAccount(provider.connection, payer, otherMint, user2.publicKey),
+          treasuryTokenAccount: getAssociatedTokenAddressSync(otherMint, findTreasuryPda(), true),
+        })
+        .signers([user2])
+        .rpc()
+    ).rejects.toThrow(/UnsupportedPremiumMint/);
+
+    const PAUSE_PREMIUM = 1 << 5;
+    await program.methods
+      .setPaused(PAUSE_PREMIUM)
+      .accountsStrict({
+        authority: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+    await expect(
+      program.methods
+        .purchasePremium()
+        .accountsStrict(purchaseAccounts)
+        .signers([user2])
+        .rpc()
+    ).rejects.toThrow(/ProgramPaused/);
+    await program.methods
+      .setPaused(0)
+      .accountsStrict({
+        authority: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+
+    await program.methods
+      .purchasePremium()
+      .accountsStrict(purchaseAccounts)
+      .signers([user2])
+      .rpc()
+
+    const firstUntil = (await program.account.userProfile.fetch(findProfilePda(user2.publicKey))).premiumUntil;
+
+    await program.methods
+      .purchasePremium()
+      .accountsStrict(purchaseAccounts)
+      .signers([user2])
+      .rpc()
+
+    const secondUntil = (await program.account.userProfile.fetch(findProfilePda(user2.publicKey))).premiumUntil;
+
+    expect(secondUntil.sub(firstUntil).toNumber()).toBe(PREMIUM_PERIOD);
+    expect(Number((await getAccount(provider.connection, treasuryTokenAccount)).amount)).toBe(20);
+  })
+
+  it('publish encryption keys', async () => {
+    for (const user of [user1, user2, user3, user4]) {
+      const signature = await program.methods
+        .setEncryptionKey([...encryptionKey(user.publicKey).publicKey])
+        .accountsStrict({
+          config: findConfigPda(),
+          user: user.publicKey,
+          profile: findProfilePda(user.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc({ commitment: "confirmed" })
+
+      // The event carries the key itself, so older versions can be found after a rotation
+      const [event] = await emittedEvents(signature);
+      expect(event.name).toBe("encryptionKeyPublished");
+      expect(Buffer.from(event.data.encryptionKey)).toEqual(Buffer.from(encryptionKey(user.publicKey).publicKey));
+
+      const profile = await program.account.userProfile.fetch(findProfilePda(user.publicKey));
+      expect(profile.encryptionKeyVersion).toBe(1);
+      expect(Buffer.from(profile.encryptionKey)).toEqual(Buffer.from(encryptionKey(user.publicKey).publicKey));
+    }
+  })
+
+  it('deactivate and reactivate user2 profile', async () => {
+    const [profilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user2.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .deactivateProfile()
+      .accountsStrict({
+        config: findConfigPda(),
+        user: user2.publicKey,
+        profile: profilePDA,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    let profile = await program.account.userProfile.fetch(profilePDA);
+    expect(profile.isActive).toBe(false);
+    expect(profile.deactivatedAt).not.toBeNull();
+
+    await program.methods
+      .reactivateProfile()
+      .accountsStrict({
+        config: findConfigPda(),
+        user: user2.publicKey,
+        profile: profilePDA,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    profile = await program.account.userProfile.fetch(profilePDA);
+    expect(profile.isActive).toBe(true);
+    expect(profile.deactivatedAt).toBeNull();
+  })
+
   it('send like from user1 to user2', async () => {
     const [user1ProfilePda] = PublicKey.findProgramAddressSync(
       [Buffer.from("profile"), user1.publicKey.toBuffer()],
@@ -149,10 +762,15 @@ describe('Soldate', () => {
       await program.methods
         .sendLike(user2.publicKey)
         .accountsStrict({
+          config: findConfigPda(),
           sender: user1.publicKey,
           senderProfile: user1ProfilePda,
           targetProfile: user2ProfilePda,
           like: likePda,
+          reverseLike: findLikePda(user2.publicKey, user1.publicKey),
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          targetBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          matchAccount: null,
           systemProgram: SystemProgram.programId,
         })
         .signers([user1])
@@ -162,7 +780,7 @@ describe('Soldate', () => {
       console.log("user1 like: ", like);
 
       const user1Profile = await program.account.userProfile.fetch(user1ProfilePda);
-      console.log("user1 matches after first like: ", user1Profile.matches);
+      console.log("user1 match count after first like: ", user1Profile.matchCount);
     } catch (error) {
       console.log("Send like error:", error);
       throw error;
@@ -191,32 +809,40 @@ describe('Soldate', () => {
     );
 
     try {
-      await program.methods
+      const signature = await program.methods
         .sendLike(user1.publicKey)
         .accountsStrict({
+          config: findConfigPda(),
           sender: user2.publicKey,
           senderProfile: user2ProfilePda,
           targetProfile: user1ProfilePda,
           like: likePda,
+          reverseLike: reverseLikePda,
+          senderBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          targetBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
           systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts([
-          {
-            pubkey: reverseLikePda,
-            isWritable: false,
-            isSigner: false,
-          }
-        ])
         .signers([user2])
-        .rpc()
+        .rpc({ commitment: "confirmed" })
 
       const like = await program.account.like.fetch(likePda);
       console.log("user2 like (mutual): ", like);
 
+      const events = await emittedEvents(signature);
+      expect(events.map(({ name }) => name)).toEqual(["matchCreated", "likeSent"]);
+
+      const reverseLike = await program.account.like.fetch(reverseLikePda);
+      expect(like.isMutual).toBe(true);
+      expect(reverseLike.isMutual).toBe(true);
+
       const user1Profile = await program.account.userProfile.fetch(user1ProfilePda);
       const user2Profile = await program.account.userProfile.fetch(user2ProfilePda);
-      console.log("user1 profile matches: ", user1Profile.matches);
-      console.log("user2 profile matches: ", user2Profile.matches);
+      console.log("user1 profile match count: ", user1Profile.matchCount);
+      console.log("user2 profile match count: ", user2Profile.matchCount);
+
+      const match = await program.account.match.fetch(findMatchPda(user1.publicKey, user2.publicKey));
+      console.log("user1/user2 match: ", match);
     } catch (error) {
       console.log("Send mutual like error:", error);
       throw error;
@@ -226,7 +852,6 @@ describe('Soldate', () => {
   it('send message from user1 to user2', async () => {
     // Use a much shorter message to avoid memory issues
     const content = "Hey there! Nice to meet you."
-    const messageId = Math.floor(Date.now() / 1000);
 
     const [user1ProfilePda] = PublicKey.findProgramAddressSync(
       [Buffer.from("profile"), user1.publicKey.toBuffer()],
@@ -238,56 +863,31 @@ describe('Soldate', () => {
       program.programId
     );
 
-    const [user1LikePda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("like"), user1.publicKey.toBuffer(), user2.publicKey.toBuffer()],
-      program.programId
-    );
-
-    const [user2LikePda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("like"), user2.publicKey.toBuffer(), user1.publicKey.toBuffer()],
-      program.programId
-    );
-
-    const messageIdBuffer = Buffer.allocUnsafe(8);
-    messageIdBuffer.writeBigUInt64LE(BigInt(messageId), 0);
-
-    const [messagePda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("message"),
-        user1.publicKey.toBuffer(),
-        user2.publicKey.toBuffer(),
-        messageIdBuffer
-      ],
-      program.programId
-    );
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+    const pagePda = await nextPagePda(conversationPda);
 
     try {
       await program.methods
-        .sendMessage(new anchor.BN(messageId), content)
+        .sendMessage(sealMessage(user1.publicKey, user2.publicKey, content))
         .accountsStrict({
+          config: findConfigPda(),
           sender: user1.publicKey,
           senderProfile: user1ProfilePda,
           receiverProfile: user2ProfilePda,
-          message: messagePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          receiverBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
           systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts([
-          {
-            pubkey: user1LikePda,
-            isWritable: false,
-            isSigner: false,
-          },
-          {
-            pubkey: user2LikePda,
-            isWritable: false,
-            isSigner: false,
-          }
-        ])
         .signers([user1])
         .rpc()
 
-      const messageData = await program.account.messageAccount.fetch(messagePda);
-      console.log("message data from user1: ", messageData);
+      const page = await program.account.messagePage.fetch(pagePda);
+      console.log("message data from user1: ", decodeEntry(page.entries[0], user1.publicKey, user2.publicKey));
+      expect(decodeEntry(page.entries[0], user1.publicKey, user2.publicKey)).toBe(content);
+      expect(Buffer.from(page.entries[0].ciphertext).includes(Buffer.from(content))).toBe(false);
     } catch (error) {
       console.log("Send message error:", error);
       throw error;
@@ -296,7 +896,6 @@ describe('Soldate', () => {
 
   it('send message from user2 to user1', async () => {
     const content = "Great to meet you too!"
-    const messageId = Math.floor(Date.now() / 1000) + 1;
 
     const [user1ProfilePda] = PublicKey.findProgramAddressSync(
       [Buffer.from("profile"), user1.publicKey.toBuffer()],
@@ -308,63 +907,601 @@ describe('Soldate', () => {
       program.programId
     );
 
-    const [user1LikePda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("like"), user1.publicKey.toBuffer(), user2.publicKey.toBuffer()],
-      program.programId
-    );
-
-    const [user2LikePda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("like"), user2.publicKey.toBuffer(), user1.publicKey.toBuffer()],
-      program.programId
-    );
-
-    const messageIdBuffer = Buffer.allocUnsafe(8);
-    messageIdBuffer.writeBigUInt64LE(BigInt(messageId), 0);
-
-    const [messagePda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("message"),
-        user2.publicKey.toBuffer(),
-        user1.publicKey.toBuffer(),
-        messageIdBuffer
-      ],
-      program.programId
-    );
+    const conversationPda = findConversationPda(user2.publicKey, user1.publicKey);
+    const pagePda = await nextPagePda(conversationPda);
 
     try {
       await program.methods
-        .sendMessage(new anchor.BN(messageId), content)
+        .sendMessage(sealMessage(user2.publicKey, user1.publicKey, content))
         .accountsStrict({
+          config: findConfigPda(),
           sender: user2.publicKey,
           senderProfile: user2ProfilePda,
           receiverProfile: user1ProfilePda,
-          message: messagePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          matchAccount: findMatchPda(user2.publicKey, user1.publicKey),
           systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts([
-          {
-            pubkey: user1LikePda,
-            isWritable: false,
-            isSigner: false,
-          },
-          {
-            pubkey: user2LikePda,
-            isWritable: false,
-            isSigner: false,
-          }
-        ])
         .signers([user2])
         .rpc()
 
-      const messageData = await program.account.messageAccount.fetch(messagePda);
-      console.log("message data from user2: ", messageData);
+      const page = await program.account.messagePage.fetch(pagePda);
+      console.log("message data from user2: ", decodeEntry(page.entries[1], user2.publicKey, user1.publicKey));
+
+      const conversation = await program.account.conversation.fetch(conversationPda);
+      expect(conversation.nextSeq.toNumber()).toBe(2);
+      expect(page.count).toBe(2);
+      expect(decodeEntry(page.entries[1], user2.publicKey, user1.publicKey)).toBe(content);
     } catch (error) {
       console.log("Send message from user2 error:", error);
       throw error;
     }
   })
 
-  it('test messaging with only one-way like', async () => {
+  it('matched users can tip each other in SOL', async () => {
+    const amount = new anchor.BN(LAMPORTS_PER_SOL / 100);
+    const tipAccounts = {
+      sender: user1.publicKey,
+      config: findConfigPda(),
+      senderProfile: findProfilePda(user1.publicKey),
+      receiverProfile: findProfilePda(user2.publicKey),
+      receiver: user2.publicKey,
+      senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+      receiverBlock: findBlockPda(user2.publicKey, user1.publicKey),
+      matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+      mint: null,
+      senderTokenAccount: null,
+      receiverTokenAccount: null,
+      tokenProgram: null,
+      systemProgram: SystemProgram.programId,
+    };
+
+    await expect(
+      program.methods
+        .sendTip(amount, "x".repeat(65))
+        .accountsStrict(tipAccounts)
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/TipNoteTooLong/);
+
+    const receiverBefore = await provider.connection.getBalance(user2.publicKey);
+    const signature = await program.methods
+      .sendTip(amount, "Coffee is on me")
+      .accountsStrict(tipAccounts)
+      .signers([user1])
+      .rpc({ commitment: "confirmed" })
+
+    expect(await provider.connection.getBalance(user2.publicKey)).toBe(receiverBefore + amount.toNumber());
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("tipSent");
+    expect(event.data.mint).toBeNull();
+    expect(event.data.note).toBe("Coffee is on me");
+  })
+
+  it('date escrow refunds both deposits when both attest', async () => {
+    const [first, second] = Buffer.compare(user1.publicKey.toBuffer(), user2.publicKey.toBuffer()) < 0
+      ? [user1.publicKey, user2.publicKey]
+      : [user2.publicKey, user1.publicKey];
+    const datePda = PublicKey.findProgramAddressSync(
+      [Buffer.from("date"), first.toBuffer(), second.toBuffer()],
+      program.programId
+    )[0];
+    const deposit = new anchor.BN(LAMPORTS_PER_SOL / 10);
+    const now = (await provider.connection.getBlockTime(await provider.connection.getSlot()))!;
+
+    await program.methods
+      .proposeDate(user2.publicKey, new anchor.BN(now + 5), "Blue Bottle, 5th street", deposit)
+      .accountsStrict({
+        proposer: user1.publicKey,
+        config: findConfigPda(),
+        proposerProfile: findProfilePda(user1.publicKey),
+        inviteeProfile: findProfilePda(user2.publicKey),
+        proposerBlock: findBlockPda(user1.publicKey, user2.publicKey),
+        inviteeBlock: findBlockPda(user2.publicKey, user1.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        date: datePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user1])
+      .rpc()
+
+    await program.methods
+      .acceptDate()
+      .accountsStrict({
+        invitee: user2.publicKey,
+        config: findConfigPda(),
+        inviteeProfile: findProfilePda(user2.publicKey),
+        proposerProfile: findProfilePda(user1.publicKey),
+        inviteeBlock: findBlockPda(user2.publicKey, user1.publicKey),
+        proposerBlock: findBlockPda(user1.publicKey, user2.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        date: datePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    await expect(
+      program.methods
+        .attestDate(true)
+        .accountsStrict({ user: user1.publicKey, date: datePda })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/DateNotStarted/);
+
+    // Attestations only open once the date time has passed on chain
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    for (const user of [user1, user2]) {
+      await program.methods
+        .attestDate(true)
+        .accountsStrict({ user: user.publicKey, date: datePda })
+        .signers([user])
+        .rpc()
+    }
+
+    const inviteeBefore = await provider.connection.getBalance(user2.publicKey);
+    await program.methods
+      .resolveDate()
+      .accountsStrict({
+        proposer: user1.publicKey,
+        invitee: user2.publicKey,
+        date: datePda,
+      })
+      .rpc()
+
+    const date = await program.account.dateEscrow.fetch(datePda);
+    expect(date.outcome).toEqual({ attended: {} });
+    expect(await provider.connection.getBalance(user2.publicKey)).toBe(inviteeBefore + deposit.toNumber());
+  }, 30000)
+
+  it('reviews need an attended date or a match', async () => {
+    const [first, second] = Buffer.compare(user1.publicKey.toBuffer(), user2.publicKey.toBuffer()) < 0
+      ? [user1.publicKey, user2.publicKey]
+      : [user2.publicKey, user1.publicKey];
+    const datePda = PublicKey.findProgramAddressSync(
+      [Buffer.from("date"), first.toBuffer(), second.toBuffer()],
+      program.programId
+    )[0];
+    const rating = { respectful: true, asPictured: true, wouldMeetAgain: false };
+
+    await expect(
+      program.methods
+        .submitReview(user2.publicKey, rating)
+        .accountsStrict({
+          reviewer: user3.publicKey,
+          config: findConfigPda(),
+          reviewerProfile: findProfilePda(user3.publicKey),
+          revieweeProfile: findProfilePda(user2.publicKey),
+          revieweeRecord: findRecordPda(user2.publicKey),
+          date: null,
+          matchAccount: null,
+          review: findReviewPda(user3.publicKey, user2.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/NoDateOrMatch/);
+
+    await program.methods
+      .submitReview(user2.publicKey, rating)
+      .accountsStrict({
+        reviewer: user1.publicKey,
+        config: findConfigPda(),
+        reviewerProfile: findProfilePda(user1.publicKey),
+        revieweeProfile: findProfilePda(user2.publicKey),
+        revieweeRecord: findRecordPda(user2.publicKey),
+        date: datePda,
+        matchAccount: null,
+        review: findReviewPda(user1.publicKey, user2.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user1])
+      .rpc()
+
+    const review = await program.account.review.fetch(findReviewPda(user1.publicKey, user2.publicKey));
+    expect(review.date?.equals(datePda)).toBe(true);
+
+    const { reputation } = await program.account.userRecord.fetch(findRecordPda(user2.publicKey));
+    expect(reputation.reviews).toBe(1);
+    expect(reputation.verifiedReviews).toBe(1);
+    expect(reputation.respectful).toBe(1);
+    expect(reputation.wouldMeetAgain).toBe(0);
+
+    // The reviewee cannot take the date away before reviewing back
+    const closeAccounts = {
+      user: user2.publicKey,
+      proposer: user1.publicKey,
+      date: datePda,
+      proposerReview: findReviewPda(user1.publicKey, user2.publicKey),
+      inviteeReview: findReviewPda(user2.publicKey, user1.publicKey),
+    };
+    await expect(
+      program.methods
+        .closeDate()
+        .accountsStrict(closeAccounts)
+        .signers([user2])
+        .rpc()
+    ).rejects.toThrow(/ReviewWindowOpen/);
+
+    await program.methods
+      .submitReview(user1.publicKey, { respectful: true, asPictured: true, wouldMeetAgain: true })
+      .accountsStrict({
+        reviewer: user2.publicKey,
+        config: findConfigPda(),
+        reviewerProfile: findProfilePda(user2.publicKey),
+        revieweeProfile: findProfilePda(user1.publicKey),
+        revieweeRecord: findRecordPda(user1.publicKey),
+        date: datePda,
+        matchAccount: null,
+        review: findReviewPda(user2.publicKey, user1.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    await program.methods
+      .closeDate()
+      .accountsStrict(closeAccounts)
+      .signers([user2])
+      .rpc()
+
+    expect(await program.account.dateEscrow.fetchNullable(datePda)).toBeNull();
+  })
+
+  it('date escrow pays the no-show deposit to the other side', async () => {
+    const [first, second] = Buffer.compare(user1.publicKey.toBuffer(), user2.publicKey.toBuffer()) < 0
+      ? [user1.publicKey, user2.publicKey]
+      : [user2.publicKey, user1.publicKey];
+    const datePda = PublicKey.findProgramAddressSync(
+      [Buffer.from("date"), first.toBuffer(), second.toBuffer()],
+      program.programId
+    )[0];
+    const deposit = new anchor.BN(LAMPORTS_PER_SOL / 10);
+    const now = (await provider.connection.getBlockTime(await provider.connection.getSlot()))!;
+
+    await program.methods
+      .proposeDate(user2.publicKey, new anchor.BN(now + 5), "Blue Bottle, 5th street", deposit)
+      .accountsStrict({
+        proposer: user1.publicKey,
+        config: findConfigPda(),
+        proposerProfile: findProfilePda(user1.publicKey),
+        inviteeProfile: findProfilePda(user2.publicKey),
+        proposerBlock: findBlockPda(user1.publicKey, user2.publicKey),
+        inviteeBlock: findBlockPda(user2.publicKey, user1.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        date: datePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user1])
+      .rpc()
+
+    await program.methods
+      .acceptDate()
+      .accountsStrict({
+        invitee: user2.publicKey,
+        config: findConfigPda(),
+        inviteeProfile: findProfilePda(user2.publicKey),
+        proposerProfile: findProfilePda(user1.publicKey),
+        inviteeBlock: findBlockPda(user2.publicKey, user1.publicKey),
+        proposerBlock: findBlockPda(user1.publicKey, user2.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        date: datePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    await new Promise((resolve) => setTimeout(resolve, 6000));
+
+    // The proposer says user2 never came, user2 has nothing against the proposer
+    await program.methods
+      .attestDate(false)
+      .accountsStrict({ user: user1.publicKey, date: datePda })
+      .signers([user1])
+      .rpc()
+    await program.methods
+      .attestDate(true)
+      .accountsStrict({ user: user2.publicKey, date: datePda })
+      .signers([user2])
+      .rpc()
+
+    const proposerBefore = await provider.connection.getBalance(user1.publicKey);
+    const inviteeBefore = await provider.connection.getBalance(user2.publicKey);
+    await program.methods
+      .resolveDate()
+      .accountsStrict({
+        proposer: user1.publicKey,
+        invitee: user2.publicKey,
+        date: datePda,
+      })
+      .rpc()
+
+    const date = await program.account.dateEscrow.fetch(datePda);
+    expect(date.outcome).toEqual({ inviteeNoShow: {} });
+    expect(await provider.connection.getBalance(user1.publicKey)).toBe(proposerBefore + 2 * deposit.toNumber());
+    expect(await provider.connection.getBalance(user2.publicKey)).toBe(inviteeBefore);
+
+    const signature = await program.methods
+      .closeDate()
+      .accountsStrict({
+        user: user1.publicKey,
+        proposer: user1.publicKey,
+        date: datePda,
+        proposerReview: findReviewPda(user1.publicKey, user2.publicKey),
+        inviteeReview: findReviewPda(user2.publicKey, user1.publicKey),
+      })
+      .signers([user1])
+      .rpc({ commitment: "confirmed" })
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("dateClosed");
+    expect(event.data.date.equals(datePda)).toBe(true);
+  }, 30000)
+
+  it('receiver marks the conversation read', async () => {
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+
+    await program.methods
+      .markRead(new anchor.BN(1))
+      .accountsStrict({
+        reader: user1.publicKey,
+        conversation: conversationPda,
+      })
+      .signers([user1])
+      .rpc()
+
+    const conversation = await program.account.conversation.fetch(conversationPda);
+    const [ownCursor, otherCursor] = conversation.userA.equals(user1.publicKey)
+      ? [conversation.readCursorA, conversation.readCursorB]
+      : [conversation.readCursorB, conversation.readCursorA];
+    expect(ownCursor.toNumber()).toBe(2);
+    expect(otherCursor.toNumber()).toBe(0);
+
+    await expect(
+      program.methods
+        .markRead(new anchor.BN(2))
+        .accountsStrict({
+          reader: user1.publicKey,
+          conversation: conversationPda,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/InvalidSequence/);
+  })
+
+  it('edit, hide and delete a message', async () => {
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+    const pagePda = findPagePda(conversationPda, 0);
+    const edited = "Hey there! Lovely to meet you."
+
+    await program.methods
+      .editMessage(new anchor.BN(0), sealMessage(user1.publicKey, user2.publicKey, edited))
+      .accountsStrict({
+        config: findConfigPda(),
+        sender: user1.publicKey,
+        senderProfile: findProfilePda(user1.publicKey),
+        receiverProfile: findProfilePda(user2.publicKey),
+        conversation: conversationPda,
+        page: pagePda,
+        senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+        receiverBlock: findBlockPda(user2.publicKey, user1.publicKey),
+      })
+      .signers([user1])
+      .rpc()
+
+    let page = await program.account.messagePage.fetch(pagePda);
+    expect(decodeEntry(page.entries[0], user1.publicKey, user2.publicKey)).toBe(edited);
+    expect(page.entries[0].editedAt.toNumber()).toBeGreaterThan(0);
+
+    // The receiver of message 0 can only hide it from their side
+    await program.methods
+      .deleteMessage(new anchor.BN(0))
+      .accountsStrict({
+        user: user2.publicKey,
+        conversation: conversationPda,
+        page: pagePda,
+      })
+      .signers([user2])
+      .rpc()
+
+    page = await program.account.messagePage.fetch(pagePda);
+    expect(page.entries[0].flags).toBe(0b10);
+    expect(page.entries[0].len).toBeGreaterThan(0);
+
+    // Deleting as the sender refunds the message deposit held in the page
+    const deposits = page.deposits[0].add(page.deposits[1]);
+    const senderBalance = await provider.connection.getBalance(user1.publicKey);
+    await program.methods
+      .deleteMessage(new anchor.BN(0))
+      .accountsStrict({
+        user: user1.publicKey,
+        conversation: conversationPda,
+        page: pagePda,
+      })
+      .signers([user1])
+      .rpc()
+
+    page = await program.account.messagePage.fetch(pagePda);
+    expect(page.entries[0].flags & 0b01).toBe(1);
+    expect(page.entries[0].len).toBe(0);
+    expect(page.count).toBe(2);
+    expect(page.deposits[0].add(page.deposits[1]).toString()).toBe(deposits.sub(page.slotDeposit).toString());
+    expect(await provider.connection.getBalance(user1.publicKey)).toBe(senderBalance + page.slotDeposit.toNumber());
+
+    await expect(
+      program.methods
+        .editMessage(new anchor.BN(0), sealMessage(user1.publicKey, user2.publicKey, "Take two"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user1.publicKey,
+          senderProfile: findProfilePda(user1.publicKey),
+          receiverProfile: findProfilePda(user2.publicKey),
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          receiverBlock: findBlockPda(user2.publicKey, user1.publicKey),
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/MessageDeleted/);
+  })
+
+  it('rejects messages sealed to an outdated key', async () => {
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+    const pagePda = await nextPagePda(conversationPda);
+
+    await expect(
+      program.methods
+        .sendMessage(sealMessage(user1.publicKey, user2.publicKey, "Old key", 0))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user1.publicKey,
+          senderProfile: findProfilePda(user1.publicKey),
+          receiverProfile: findProfilePda(user2.publicKey),
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          receiverBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/StaleEncryptionKey/);
+  })
+
+  it('cannot close a message page before it is full', async () => {
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+
+    await expect(
+      program.methods
+        .closeMessagePage(new anchor.BN(0))
+        .accountsStrict({
+          payer: user1.publicKey,
+          payerProfile: findProfilePda(user1.publicKey),
+          conversation: conversationPda,
+          other: user2.publicKey,
+          page: findPagePda(conversationPda, 0),
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/PageNotFull/);
+  })
+
+  it('a full page closes once both sides read it', async () => {
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+    const sendMessage = async (sender: Keypair, receiver: Keypair, text: string) =>
+      program.methods
+        .sendMessage(sealMessage(sender.publicKey, receiver.publicKey, text))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: sender.publicKey,
+          senderProfile: findProfilePda(sender.publicKey),
+          receiverProfile: findProfilePda(receiver.publicKey),
+          conversation: conversationPda,
+          page: await nextPagePda(conversationPda),
+          senderBlock: findBlockPda(sender.publicKey, receiver.publicKey),
+          receiverBlock: findBlockPda(receiver.publicKey, sender.publicKey),
+          matchAccount: findMatchPda(sender.publicKey, receiver.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([sender])
+        .rpc()
+
+    // Fill page 0, then leave one message from each side on page 1
+    let conversation = await program.account.conversation.fetch(conversationPda);
+    while (conversation.nextSeq.toNumber() < MESSAGES_PER_PAGE) {
+      await sendMessage(user1, user2, `Message ${conversation.nextSeq.toNumber()}`);
+      conversation = await program.account.conversation.fetch(conversationPda);
+    }
+    await sendMessage(user1, user2, "Next page");
+    await sendMessage(user2, user1, "Still here");
+
+    const pagePda = findPagePda(conversationPda, 0);
+    await expect(
+      program.methods
+        .closeMessagePage(new anchor.BN(0))
+        .accountsStrict({
+          payer: user1.publicKey,
+          payerProfile: findProfilePda(user1.publicKey),
+          conversation: conversationPda,
+          other: user2.publicKey,
+          page: pagePda,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/PageUnread/);
+
+    // Reading and closing pages stay open while messages are paused, so the rent can still be reclaimed
+    const PAUSE_MESSAGES = 1 << 2;
+    await program.methods
+      .setPaused(PAUSE_MESSAGES)
+      .accountsStrict({
+        authority: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+    await expect(sendMessage(user2, user1, "Paused")).rejects.toThrow(/ProgramPaused/);
+
+    for (const reader of [user1, user2]) {
+      await program.methods
+        .markRead(new anchor.BN(MESSAGES_PER_PAGE - 1))
+        .accountsStrict({
+          reader: reader.publicKey,
+          conversation: conversationPda,
+        })
+        .signers([reader])
+        .rpc()
+    }
+
+    conversation = await program.account.conversation.fetch(conversationPda);
+    const page = await program.account.messagePage.fetch(pagePda);
+    const user2Deposits = page.deposits[conversation.userA.equals(user2.publicKey) ? 0 : 1].toNumber();
+    const pageLamports = await provider.connection.getBalance(pagePda);
+    const messagePages = (await program.account.userProfile.fetch(findProfilePda(user1.publicKey))).messagePages;
+    const user1Balance = await provider.connection.getBalance(user1.publicKey);
+    const user2Balance = await provider.connection.getBalance(user2.publicKey);
+
+    const signature = await program.methods
+      .closeMessagePage(new anchor.BN(0))
+      .accountsStrict({
+        payer: user1.publicKey,
+        payerProfile: findProfilePda(user1.publicKey),
+        conversation: conversationPda,
+        other: user2.publicKey,
+        page: pagePda,
+      })
+      .signers([user1])
+      .rpc({ commitment: "confirmed" })
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+
+    expect(await program.account.messagePage.fetchNullable(pagePda)).toBeNull();
+    // user2's deposits go back to user2, the rent and user1's own deposits to user1
+    expect(await provider.connection.getBalance(user2.publicKey)).toBe(user2Balance + user2Deposits);
+    expect(await provider.connection.getBalance(user1.publicKey)).toBe(user1Balance + pageLamports - user2Deposits - tx!.meta!.fee);
+
+    const closed = await program.account.conversation.fetch(conversationPda);
+    expect(closed.openPages).toBe(conversation.openPages - 1);
+    expect((await program.account.userProfile.fetch(findProfilePda(user1.publicKey))).messagePages).toBe(messagePages - 1);
+
+    await program.methods
+      .setPaused(0)
+      .accountsStrict({
+        authority: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+  }, 60000)
+
+  it('send intro message with only one-way like', async () => {
     const content = "Hello from user3!"
 
     const [user1ProfilePda] = PublicKey.findProgramAddressSync(
@@ -385,10 +1522,15 @@ describe('Soldate', () => {
     await program.methods
       .sendLike(user1.publicKey)
       .accountsStrict({
+        config: findConfigPda(),
         sender: user3.publicKey,
         senderProfile: user3ProfilePda,
         targetProfile: user1ProfilePda,
         like: user3LikesPda,
+        reverseLike: findLikePda(user1.publicKey, user3.publicKey),
+        senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+        targetBlock: findBlockPda(user1.publicKey, user3.publicKey),
+        matchAccount: null,
         systemProgram: SystemProgram.programId,
       })
       .signers([user3])
@@ -396,66 +1538,775 @@ describe('Soldate', () => {
 
     console.log("user3 liked user1");
 
-    const messageId = Math.floor(Date.now() / 1000) + 2;
     
-    const messageIdBuffer = Buffer.allocUnsafe(8);
-    messageIdBuffer.writeBigUInt64LE(BigInt(messageId), 0);
-    
-    const [messagePda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("message"),
-        user3.publicKey.toBuffer(),
-        user1.publicKey.toBuffer(),
-        messageIdBuffer
-      ],
-      program.programId
-    );
+    const conversationPda = findConversationPda(user3.publicKey, user1.publicKey);
+    const pagePda = await nextPagePda(conversationPda);
+
+    // A one-way like is not enough for a regular message
+    await expect(
+      program.methods
+        .sendMessage(sealMessage(user3.publicKey, user1.publicKey, content))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user3.publicKey,
+          senderProfile: user3ProfilePda,
+          receiverProfile: user1ProfilePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          matchAccount: findMatchPda(user1.publicKey, user3.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/NotMutualLikes/);
 
     try {
       await program.methods
-        .sendMessage(new anchor.BN(messageId), content)
+        .sendIntroMessage(sealMessage(user3.publicKey, user1.publicKey, content))
         .accountsStrict({
+          config: findConfigPda(),
           sender: user3.publicKey,
           senderProfile: user3ProfilePda,
           receiverProfile: user1ProfilePda,
-          message: messagePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          like: user3LikesPda,
           systemProgram: SystemProgram.programId,
         })
-        .remainingAccounts([
-          {
-            pubkey: user3LikesPda,
-            isWritable: false,
-            isSigner: false,
-          }
-        ])
         .signers([user3])
         .rpc()
 
-      const messageData = await program.account.messageAccount.fetch(messagePda);
-      console.log("message from user3 with one-way like: ", messageData);
+      const page = await program.account.messagePage.fetch(pagePda);
+      console.log("intro message from user3 with one-way like: ", decodeEntry(page.entries[0], user3.publicKey, user1.publicKey));
     } catch (error) {
       console.log("One-way like message error:", error);
       throw error;
     }
-  })
 
-  it('block user', async () => {
-    const [blockPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("block"), user1.publicKey.toBuffer(), user3.publicKey.toBuffer()],
-      program.programId
-    );
+    const conversation = await program.account.conversation.fetch(conversationPda);
+    expect(conversation.introSent[conversation.userA.equals(user3.publicKey) ? 0 : 1]).toBe(true);
 
+    // Taking the like back and liking again does not buy another intro
     await program.methods
-      .blockUser(user3.publicKey)
+      .unlike(user1.publicKey)
       .accountsStrict({
-        blocker: user1.publicKey,
-        block: blockPDA,
-        systemProgram: SystemProgram.programId
+        sender: user3.publicKey,
+        senderProfile: user3ProfilePda,
+        targetProfile: user1ProfilePda,
+        like: user3LikesPda,
+        reverseLike: findLikePda(user1.publicKey, user3.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user3.publicKey),
+        systemProgram: SystemProgram.programId,
       })
-      .signers([user1])
+      .signers([user3])
       .rpc()
 
-    const block = await program.account.blockedUser.fetch(blockPDA);
-    console.log("block: ", block);
+    await program.methods
+      .sendLike(user1.publicKey)
+      .accountsStrict({
+        config: findConfigPda(),
+        sender: user3.publicKey,
+        senderProfile: user3ProfilePda,
+        targetProfile: user1ProfilePda,
+        like: user3LikesPda,
+        reverseLike: findLikePda(user1.publicKey, user3.publicKey),
+        senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+        targetBlock: findBlockPda(user1.publicKey, user3.publicKey),
+        matchAccount: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user3])
+      .rpc()
+
+    await expect(
+      program.methods
+        .sendIntroMessage(sealMessage(user3.publicKey, user1.publicKey, "Hello again"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user3.publicKey,
+          senderProfile: user3ProfilePda,
+          receiverProfile: user1ProfilePda,
+          conversation: conversationPda,
+          page: await nextPagePda(conversationPda),
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          like: user3LikesPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/IntroAlreadySent/);
+  })
+
+  it('super like pays the treasury and the admin withdraws it', async () => {
+    const price = new anchor.BN(LAMPORTS_PER_SOL / 100);
+
+    await program.methods
+      .setSuperLikePrice(price)
+      .accountsStrict({
+        admin: provider.wallet.publicKey,
+        config: findConfigPda(),
+      })
+      .rpc()
+
+    const treasuryBefore = await provider.connection.getBalance(findTreasuryPda());
+
+    await program.methods
+      .sendSuperLike(user2.publicKey)
+      .accountsStrict({
+        base: {
+          config: findConfigPda(),
+          sender: user3.publicKey,
+          senderProfile: findProfilePda(user3.publicKey),
+          targetProfile: findProfilePda(user2.publicKey),
+          like: findLikePda(user3.publicKey, user2.publicKey),
+          reverseLike: findLikePda(user2.publicKey, user3.publicKey),
+          senderBlock: findBlockPda(user3.publicKey, user2.publicKey),
+          targetBlock: findBlockPda(user2.publicKey, user3.publicKey),
+          matchAccount: null,
+          systemProgram: SystemProgram.programId,
+        },
+        treasury: findTreasuryPda(),
+      })
+      .signers([user3])
+      .rpc()
+
+    const like = await program.account.like.fetch(findLikePda(user3.publicKey, user2.publicKey));
+    expect(like.kind).toEqual({ super: {} });
+    expect(await provider.connection.getBalance(findTreasuryPda())).toBe(treasuryBefore + price.toNumber());
+
+    const withdrawAccounts = {
+      admin: provider.wallet.publicKey,
+      config: findConfigPda(),
+      treasury: findTreasuryPda(),
+      recipient: provider.wallet.publicKey,
+    };
+
+    await expect(
+      program.methods
+        .withdrawTreasury(price)
+        .accountsStrict({ ...withdrawAccounts, admin: user1.publicKey })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/Unauthorized/);
+
+    await expect(
+      program.methods
+        .withdrawTreasury(price.addn(1))
+        .accountsStrict(withdrawAccounts)
+        .rpc()
+    ).rejects.toThrow(/InsufficientTreasuryBalance/);
+
+    await program.methods
+      .withdrawTreasury(price)
+      .accountsStrict(withdrawAccounts)
+      .rpc()
+
+    expect(await provider.connection.getBalance(findTreasuryPda())).toBe(treasuryBefore);
+  })
+
+  it('block user', async () => {
+    const [blockPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("block"), user1.publicKey.toBuffer(), user3.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const signature = await program.methods
+      .blockUser(user3.publicKey)
+      .accountsStrict({
+        blocker: user1.publicKey,
+        block: blockPDA,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([user1])
+      .rpc({ commitment: "confirmed" })
+
+    const block = await program.account.blockedUser.fetch(blockPDA);
+    console.log("block: ", block);
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("userBlocked");
+    expect(event.data.blocked.equals(user3.publicKey)).toBe(true);
+    expect(event.data.slot.toNumber()).toBeGreaterThan(0);
+  })
+
+  it('blocked user cannot like or message the blocker', async () => {
+    const [user1ProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user1.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [user3ProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user3.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [user3LikesPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("like"), user3.publicKey.toBuffer(), user1.publicKey.toBuffer()],
+      program.programId
+    );
+
+
+    const conversationPda = findConversationPda(user3.publicKey, user1.publicKey);
+    const pagePda = await nextPagePda(conversationPda);
+
+    await expect(
+      program.methods
+        .sendIntroMessage(sealMessage(user3.publicKey, user1.publicKey, "Are you there?"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user3.publicKey,
+          senderProfile: user3ProfilePda,
+          receiverProfile: user1ProfilePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          like: user3LikesPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/UserBlocked/);
+
+    await expect(
+      program.methods
+        .sendMessage(sealMessage(user3.publicKey, user1.publicKey, "Are you there?"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user3.publicKey,
+          senderProfile: user3ProfilePda,
+          receiverProfile: user1ProfilePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          matchAccount: findMatchPda(user1.publicKey, user3.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/UserBlocked/);
+
+    // Nor rewrite the intro that is already there
+    await expect(
+      program.methods
+        .editMessage(new anchor.BN(0), sealMessage(user3.publicKey, user1.publicKey, "Hello again"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user3.publicKey,
+          senderProfile: user3ProfilePda,
+          receiverProfile: user1ProfilePda,
+          conversation: conversationPda,
+          page: findPagePda(conversationPda, 0),
+          senderBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          receiverBlock: findBlockPda(user1.publicKey, user3.publicKey),
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/UserBlocked/);
+
+    const [likePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("like"), user1.publicKey.toBuffer(), user3.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await expect(
+      program.methods
+        .sendLike(user3.publicKey)
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user1.publicKey,
+          senderProfile: user1ProfilePda,
+          targetProfile: user3ProfilePda,
+          like: likePda,
+          reverseLike: findLikePda(user3.publicKey, user1.publicKey),
+          senderBlock: findBlockPda(user1.publicKey, user3.publicKey),
+          targetBlock: findBlockPda(user3.publicKey, user1.publicKey),
+          matchAccount: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/UserBlocked/);
+  })
+
+  it('unblock user', async () => {
+    const blockPDA = findBlockPda(user1.publicKey, user3.publicKey);
+
+    await program.methods
+      .unblockUser(user3.publicKey)
+      .accountsStrict({
+        blocker: user1.publicKey,
+        block: blockPDA,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([user1])
+      .rpc()
+
+    const block = await program.account.blockedUser.fetchNullable(blockPDA);
+    expect(block).toBeNull();
+  })
+
+  it('close user3 profile', async () => {
+    const [user3ProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user3.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await expect(
+      program.methods
+        .closeProfile()
+        .accountsStrict({
+          user: user3.publicKey,
+          profile: user3ProfilePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user3])
+        .rpc()
+    ).rejects.toThrow(/OutstandingAccounts/);
+
+    const sentBy = [{ memcmp: { offset: 8, bytes: user3.publicKey.toBase58() } }];
+    const likes = await program.account.like.all(sentBy);
+    // Pages are owned by whoever opened them, the payer follows the conversation key
+    const pages = await program.account.messagePage.all([
+      { memcmp: { offset: 8 + 32, bytes: user3.publicKey.toBase58() } },
+    ]);
+    // Pages need their conversation alongside, which is closed too once it has no pages left
+    const conversations = await program.account.conversation.all([
+      { memcmp: { offset: 8 + 64, bytes: user3.publicKey.toBase58() } },
+    ]);
+    expect(conversations).toHaveLength(1);
+
+    await program.methods
+      .closeProfile()
+      .accountsStrict({
+        user: user3.publicKey,
+        profile: user3ProfilePda,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        [...likes, ...pages, ...conversations].map(({ publicKey }) => ({
+          pubkey: publicKey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .signers([user3])
+      .rpc()
+
+    const profile = await program.account.userProfile.fetchNullable(user3ProfilePda);
+    expect(profile).toBeNull();
+    expect(await program.account.conversation.fetchNullable(conversations[0].publicKey)).toBeNull();
+  })
+
+  it('unlike tears down the user1/user2 match', async () => {
+    const [user1ProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user1.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [user2ProfilePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("profile"), user2.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [likePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("like"), user2.publicKey.toBuffer(), user1.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [reverseLikePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("like"), user1.publicKey.toBuffer(), user2.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .unlike(user1.publicKey)
+      .accountsStrict({
+        sender: user2.publicKey,
+        senderProfile: user2ProfilePda,
+        targetProfile: user1ProfilePda,
+        like: likePda,
+        reverseLike: reverseLikePda,
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    const like = await program.account.like.fetchNullable(likePda);
+    expect(like).toBeNull();
+
+    const reverseLike = await program.account.like.fetch(reverseLikePda);
+    expect(reverseLike.isMutual).toBe(false);
+
+    const user1Profile = await program.account.userProfile.fetch(user1ProfilePda);
+    const user2Profile = await program.account.userProfile.fetch(user2ProfilePda);
+    expect(user1Profile.matchCount).toBe(0);
+    expect(user2Profile.matchCount).toBe(0);
+
+    const match = await program.account.match.fetch(findMatchPda(user1.publicKey, user2.publicKey));
+    expect(match.status).toEqual({ unmatched: {} });
+  })
+
+  it('like again and unmatch user1/user2', async () => {
+    await program.methods
+      .sendLike(user1.publicKey)
+      .accountsStrict({
+        config: findConfigPda(),
+        sender: user2.publicKey,
+        senderProfile: findProfilePda(user2.publicKey),
+        targetProfile: findProfilePda(user1.publicKey),
+        like: findLikePda(user2.publicKey, user1.publicKey),
+        reverseLike: findLikePda(user1.publicKey, user2.publicKey),
+        senderBlock: findBlockPda(user2.publicKey, user1.publicKey),
+        targetBlock: findBlockPda(user1.publicKey, user2.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    let user1Profile = await program.account.userProfile.fetch(findProfilePda(user1.publicKey));
+    expect(user1Profile.matchCount).toBe(1);
+
+    await program.methods
+      .unmatch(user2.publicKey)
+      .accountsStrict({
+        user: user1.publicKey,
+        other: user2.publicKey,
+        userProfile: findProfilePda(user1.publicKey),
+        otherProfile: findProfilePda(user2.publicKey),
+        userLike: findLikePda(user1.publicKey, user2.publicKey),
+        otherLike: findLikePda(user2.publicKey, user1.publicKey),
+        matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user1])
+      .rpc()
+
+    user1Profile = await program.account.userProfile.fetch(findProfilePda(user1.publicKey));
+    const user2Profile = await program.account.userProfile.fetch(findProfilePda(user2.publicKey));
+    expect(user1Profile.matchCount).toBe(0);
+    expect(user2Profile.matchCount).toBe(0);
+
+    const likes = await program.account.like.fetchMultiple([
+      findLikePda(user1.publicKey, user2.publicKey),
+      findLikePda(user2.publicKey, user1.publicKey),
+    ]);
+    expect(likes).toEqual([null, null]);
+  })
+
+  it('cannot message after unmatch', async () => {
+
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+    const pagePda = await nextPagePda(conversationPda);
+
+    await expect(
+      program.methods
+        .sendMessage(sealMessage(user1.publicKey, user2.publicKey, "Still there?"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: user1.publicKey,
+          senderProfile: findProfilePda(user1.publicKey),
+          receiverProfile: findProfilePda(user2.publicKey),
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(user1.publicKey, user2.publicKey),
+          receiverBlock: findBlockPda(user2.publicKey, user1.publicKey),
+          matchAccount: findMatchPda(user1.publicKey, user2.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/NotMutualLikes/);
+  })
+
+  it('an unmatched pair cannot review each other', async () => {
+    // user2 returns the like, so user2 pays for the match
+    for (const [sender, target, matchAccount] of [
+      [user4, user2, null],
+      [user2, user4, findMatchPda(user2.publicKey, user4.publicKey)],
+    ] as const) {
+      await program.methods
+        .sendLike(target.publicKey)
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: sender.publicKey,
+          senderProfile: findProfilePda(sender.publicKey),
+          targetProfile: findProfilePda(target.publicKey),
+          like: findLikePda(sender.publicKey, target.publicKey),
+          reverseLike: findLikePda(target.publicKey, sender.publicKey),
+          senderBlock: findBlockPda(sender.publicKey, target.publicKey),
+          targetBlock: findBlockPda(target.publicKey, sender.publicKey),
+          matchAccount,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([sender])
+        .rpc()
+    }
+
+    await program.methods
+      .unmatch(user4.publicKey)
+      .accountsStrict({
+        user: user2.publicKey,
+        other: user4.publicKey,
+        userProfile: findProfilePda(user2.publicKey),
+        otherProfile: findProfilePda(user4.publicKey),
+        userLike: findLikePda(user2.publicKey, user4.publicKey),
+        otherLike: findLikePda(user4.publicKey, user2.publicKey),
+        matchAccount: findMatchPda(user2.publicKey, user4.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    // The unmatched record is still there but no longer backs a review
+    await expect(
+      program.methods
+        .submitReview(user2.publicKey, { respectful: false, asPictured: false, wouldMeetAgain: false })
+        .accountsStrict({
+          reviewer: user4.publicKey,
+          config: findConfigPda(),
+          reviewerProfile: findProfilePda(user4.publicKey),
+          revieweeProfile: findProfilePda(user2.publicKey),
+          revieweeRecord: findRecordPda(user2.publicKey),
+          date: null,
+          matchAccount: findMatchPda(user2.publicKey, user4.publicKey),
+          review: findReviewPda(user4.publicKey, user2.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user4])
+        .rpc()
+    ).rejects.toThrow(/NoDateOrMatch/);
+  })
+
+  it('closing a matched profile ends the match and refunds the other side', async () => {
+    for (const [sender, target, matchAccount] of [
+      [user4, user2, null],
+      [user2, user4, findMatchPda(user2.publicKey, user4.publicKey)],
+    ] as const) {
+      await program.methods
+        .sendLike(target.publicKey)
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: sender.publicKey,
+          senderProfile: findProfilePda(sender.publicKey),
+          targetProfile: findProfilePda(target.publicKey),
+          like: findLikePda(sender.publicKey, target.publicKey),
+          reverseLike: findLikePda(target.publicKey, sender.publicKey),
+          senderBlock: findBlockPda(sender.publicKey, target.publicKey),
+          targetBlock: findBlockPda(target.publicKey, sender.publicKey),
+          matchAccount,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([sender])
+        .rpc()
+    }
+
+    // user4 opens the conversation and its first page, user2's reply leaves a deposit in it
+    const conversationPda = findConversationPda(user2.publicKey, user4.publicKey);
+    const pagePda = findPagePda(conversationPda, 0);
+    for (const [sender, receiver] of [[user4, user2], [user2, user4]]) {
+      await program.methods
+        .sendMessage(sealMessage(sender.publicKey, receiver.publicKey, "Hi!"))
+        .accountsStrict({
+          config: findConfigPda(),
+          sender: sender.publicKey,
+          senderProfile: findProfilePda(sender.publicKey),
+          receiverProfile: findProfilePda(receiver.publicKey),
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(sender.publicKey, receiver.publicKey),
+          receiverBlock: findBlockPda(receiver.publicKey, sender.publicKey),
+          matchAccount: findMatchPda(sender.publicKey, receiver.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([sender])
+        .rpc()
+    }
+
+    const conversation = await program.account.conversation.fetch(conversationPda);
+    const page = await program.account.messagePage.fetch(pagePda);
+    const user2Deposits = page.deposits[conversation.userA.equals(user2.publicKey) ? 0 : 1];
+    expect(user2Deposits.toNumber()).toBeGreaterThan(0);
+
+    const matchCountBefore = (await program.account.userProfile.fetch(findProfilePda(user2.publicKey))).matchCount;
+    const user2Balance = await provider.connection.getBalance(user2.publicKey);
+
+    await program.methods
+      .closeProfile()
+      .accountsStrict({
+        user: user4.publicKey,
+        profile: findProfilePda(user4.publicKey),
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        [
+          findLikePda(user4.publicKey, user2.publicKey),
+          findMatchPda(user2.publicKey, user4.publicKey),
+          findProfilePda(user2.publicKey),
+          findLikePda(user2.publicKey, user4.publicKey),
+          pagePda,
+          conversationPda,
+          user2.publicKey,
+        ].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+      )
+      .signers([user4])
+      .rpc()
+
+    expect(await program.account.userProfile.fetchNullable(findProfilePda(user4.publicKey))).toBeNull();
+    expect(await program.account.messagePage.fetchNullable(pagePda)).toBeNull();
+    expect(await program.account.conversation.fetchNullable(conversationPda)).toBeNull();
+
+    const user2Profile = await program.account.userProfile.fetch(findProfilePda(user2.publicKey));
+    expect(user2Profile.matchCount).toBe(matchCountBefore - 1);
+
+    // user2 paid for the match, so it stays behind as unmatched until user2 closes it
+    const match = await program.account.match.fetch(findMatchPda(user2.publicKey, user4.publicKey));
+    expect(match.status).toEqual({ unmatched: {} });
+    expect(match.payer.toBase58()).toBe(user2.publicKey.toBase58());
+
+    // user2's like stays, but no longer reads as a match
+    const user2Like = await program.account.like.fetch(findLikePda(user2.publicKey, user4.publicKey));
+    expect(user2Like.isMutual).toBe(false);
+
+    expect(await provider.connection.getBalance(user2.publicKey)).toBe(user2Balance + user2Deposits.toNumber());
+  })
+
+  it('dissolved match rent goes back to its payer', async () => {
+    const matchPda = findMatchPda(user1.publicKey, user2.publicKey);
+    const match = await program.account.match.fetch(matchPda);
+    // user2 returned the like and paid for the match
+    expect(match.payer.toBase58()).toBe(user2.publicKey.toBase58());
+
+    await expect(
+      program.methods
+        .closeMatch(user2.publicKey)
+        .accountsStrict({
+          user: user1.publicKey,
+          payer: user1.publicKey,
+          matchAccount: matchPda,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/InvalidAccount/);
+
+    const payerBalance = await provider.connection.getBalance(user2.publicKey);
+    const signature = await program.methods
+      .closeMatch(user2.publicKey)
+      .accountsStrict({
+        user: user1.publicKey,
+        payer: user2.publicKey,
+        matchAccount: matchPda,
+      })
+      .signers([user1])
+      .rpc({ commitment: "confirmed" })
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("matchClosed");
+    expect(event.data.closedBy.equals(user1.publicKey)).toBe(true);
+    expect(await program.account.match.fetchNullable(matchPda)).toBeNull();
+    expect(await provider.connection.getBalance(user2.publicKey)).toBeGreaterThan(payerBalance);
+
+    // The conversation stays until its message pages are closed
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+    const conversation = await program.account.conversation.fetch(conversationPda);
+    await expect(
+      program.methods
+        .closeConversation()
+        .accountsStrict({
+          user: user1.publicKey,
+          payer: conversation.payer,
+          conversation: conversationPda,
+        })
+        .signers([user1])
+        .rpc()
+    ).rejects.toThrow(/ConversationHasPages/);
+  })
+
+  it('report a user over a message and resolve the report', async () => {
+    const reportPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("report"), user2.publicKey.toBuffer(), user1.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    const conversationPda = findConversationPda(user1.publicKey, user2.publicKey);
+
+    // Page 0 was closed, message 33 is user2's own reply
+    await expect(
+      program.methods
+        .reportUser(user1.publicKey, { harassment: {} }, new anchor.BN(33))
+        .accountsStrict({
+          reporter: user2.publicKey,
+          reporterProfile: findProfilePda(user2.publicKey),
+          reportedProfile: findProfilePda(user1.publicKey),
+          reportedRecord: findRecordPda(user1.publicKey),
+          report: reportPda,
+          conversation: conversationPda,
+          page: findPagePda(conversationPda, 1),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user2])
+        .rpc()
+    ).rejects.toThrow(/MessageNotFromReported/);
+
+    await program.methods
+      .reportUser(user1.publicKey, { harassment: {} }, new anchor.BN(32))
+      .accountsStrict({
+        reporter: user2.publicKey,
+        reporterProfile: findProfilePda(user2.publicKey),
+        reportedProfile: findProfilePda(user1.publicKey),
+        reportedRecord: findRecordPda(user1.publicKey),
+        report: reportPda,
+        conversation: conversationPda,
+        page: findPagePda(conversationPda, 1),
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc()
+
+    let report = await program.account.report.fetch(reportPda);
+    expect(report.status).toEqual({ open: {} });
+    expect(report.message?.seq.toNumber()).toBe(32);
+
+    const reported = await program.account.userRecord.fetch(findRecordPda(user1.publicKey));
+    expect(reported.reportCount).toBe(1);
+
+    await expect(
+      program.methods
+        .closeReport(user1.publicKey)
+        .accountsStrict({ reporter: user2.publicKey, report: reportPda })
+        .signers([user2])
+        .rpc()
+    ).rejects.toThrow(/ReportNotResolved/);
+
+    await program.methods
+      .updateReportStatus({ dismissed: {} })
+      .accountsStrict({
+        moderator: provider.wallet.publicKey,
+        moderatorRecord: findModeratorPda(provider.wallet.publicKey),
+        report: reportPda,
+      })
+      .rpc()
+
+    report = await program.account.report.fetch(reportPda);
+    expect(report.status).toEqual({ dismissed: {} });
+
+    const signature = await program.methods
+      .closeReport(user1.publicKey)
+      .accountsStrict({ reporter: user2.publicKey, report: reportPda })
+      .signers([user2])
+      .rpc({ commitment: "confirmed" })
+
+    const [event] = await emittedEvents(signature);
+    expect(event.name).toBe("reportClosed");
+    expect(event.data.reported.equals(user1.publicKey)).toBe(true);
+    expect(await program.account.report.fetchNullable(reportPda)).toBeNull();
   })
-})
\ No newline at end of file
+})
@@ -18,21 +18,43 @@ interface UserProfileData {
   location: string;
   isActive: boolean;
   createdAt: BN;
-  matches: PublicKey[];
+  matchCount: number;
+  encryptionKeyVersion: number;
   bump: number;
 }
 
+interface Reputation {
+  reviews: number;
+  verifiedReviews: number;
+  respectful: number;
+  asPictured: number;
+  wouldMeetAgain: number;
+}
+
+// Share of positive answers across all on-chain reviews, null until the first review
+function trustScore(reputation: Reputation | undefined): number | null {
+  if (!reputation || reputation.reviews === 0) return null;
+  const { reviews, respectful, asPictured, wouldMeetAgain } = reputation;
+  return Math.round(((respectful + asPictured + wouldMeetAgain) / (3 * reviews)) * 100);
+}
+
 interface UserProfileAccount {
   publicKey: PublicKey;
   account: UserProfileData;
 }
 
 interface MessageData {
+  conversation: PublicKey;
   sender: PublicKey;
   receiver: PublicKey;
-  content: string;
+  seq: BN;
+  nonce: number[];
+  ciphertext: number[];
   timestamp: BN;
-  bump: number;
+  editedAt: BN;
+  deleted: boolean;
+  hiddenByReceiver: boolean;
+  seen: boolean;
 }
 
 interface MessageAccount {
@@ -45,6 +67,7 @@ interface LikeData {
   receiver: PublicKey;
   timestamp: BN;
   isMutual: boolean;
+  kind: { normal?: object; super?: object };
   bump: number;
 }
 
@@ -53,6 +76,20 @@ interface LikeAccount {
   account: LikeData;
 }
 
+interface MatchData {
+  userA: PublicKey;
+  userB: PublicKey;
+  initiator: PublicKey;
+  matchedAt: BN;
+  status: { active?: object; unmatched?: object };
+  bump: number;
+}
+
+interface MatchAccount {
+  publicKey: PublicKey;
+  account: MatchData;
+}
+
 interface CreateProfileFormData {
   name: string;
   age: number;
@@ -82,7 +119,9 @@ export default function SolDateDashboard() {
 
   const {
     userProfileAccounts,
+    userRecordAccounts,
     likeAccounts,
+    matchAccounts,
     messageAccounts,
     createUserProfile
   } = useSoldateProgram();
@@ -115,6 +154,19 @@ export default function SolDateDashboard() {
     );
   }, [publicKey, likeAccounts.data]);
 
+  // Get the ids of users the current user is actively matched with
+  const matchedUserIds = useMemo((): PublicKey[] => {
+    if (!publicKey || !matchAccounts.data) return [];
+    return matchAccounts.data
+      .filter((match: MatchAccount) => match.account.status.active !== undefined)
+      .filter((match: MatchAccount) =>
+        match.account.userA.equals(publicKey) || match.account.userB.equals(publicKey)
+      )
+      .map((match: MatchAccount) =>
+        match.account.userA.equals(publicKey) ? match.account.userB : match.account.userA
+      );
+  }, [publicKey, matchAccounts.data]);
+
   // Get other user profiles for discovery (excluding users we've already interacted with)
   const otherProfiles = useMemo((): UserProfileAccount[] => {
     if (!publicKey || !userProfileAccounts.data) return [];
@@ -122,22 +174,22 @@ export default function SolDateDashboard() {
     // Get list of users we've already liked
     const likedUserIds = usersILiked.map(like => like.account.receiver.toString());
     
-    // Get list of users who are our matches (from current user's matches array)
-    const matchedUserIds = currentUserProfile?.account.matches.map(match => match.toString()) || [];
+    // Get list of users who are our matches
+    const matchedIds = matchedUserIds.map(match => match.toString());
 
     return userProfileAccounts.data.filter((profile: UserProfileAccount) => 
       !profile.account.owner.equals(publicKey) && 
       profile.account.isActive &&
       !likedUserIds.includes(profile.account.owner.toString()) &&
-      !matchedUserIds.includes(profile.account.owner.toString())
+      !matchedIds.includes(profile.account.owner.toString())
     );
-  }, [publicKey, userProfileAccounts.data, usersILiked, currentUserProfile]);
+  }, [publicKey, userProfileAccounts.data, usersILiked, matchedUserIds]);
 
-  // Get user's matches based on the matches array in their profile
+  // Get user's matches based on their active match accounts
   const userMatches = useMemo((): UserMatch[] => {
     if (!publicKey || !currentUserProfile || !userProfileAccounts.data) return [];
     
-    return currentUserProfile.account.matches.map((matchedUserId: PublicKey) => {
+    return matchedUserIds.map((matchedUserId: PublicKey) => {
       const otherUserProfile = userProfileAccounts.data.find((profile: UserProfileAccount) => 
         profile.account.owner.equals(matchedUserId)
       );
@@ -149,7 +201,7 @@ export default function SolDateDashboard() {
         otherUserId: matchedUserId
       };
     }).filter((match): match is UserMatch => Boolean(match));
-  }, [publicKey, currentUserProfile, userProfileAccounts.data]);
+  }, [publicKey, currentUserProfile, userProfileAccounts.data, matchedUserIds]);
 
   // Get messages between current user and another user
   const getMessagesWithUser = (otherUserId: PublicKey): MessageAccount[] => {
@@ -157,6 +209,8 @@ export default function SolDateDashboard() {
     
     return messageAccounts.data
       .filter((msg: MessageAccount) => {
+        // Messages the current user hid stay on-chain but are dropped from their view
+        if (msg.account.receiver.equals(publicKey) && msg.account.hiddenByReceiver) return false;
         return (msg.account.sender.equals(publicKey) && msg.account.receiver.equals(otherUserId)) ||
                (msg.account.sender.equals(otherUserId) && msg.account.receiver.equals(publicKey));
       })
@@ -422,6 +476,20 @@ export default function SolDateDashboard() {
     showActions?: boolean;
   }
 
+  const reputationOf = (owner: PublicKey): Reputation | undefined =>
+    userRecordAccounts.data?.find(({ account }) => account.owner.equals(owner))?.account.reputation;
+
+  const TrustScore = ({ owner }: { owner: PublicKey }) => {
+    const reputation = reputationOf(owner);
+    const score = trustScore(reputation);
+    if (!reputation || score === null) return null;
+    return (
+      <p className="text-sm text-gray-600 mb-3">
+        Trust {score}% · {reputation.reviews} reviews ({reputation.verifiedReviews} after a date)
+      </p>
+    );
+  };
+
   const ProfileCard = ({ profile, onLike, onPass, showActions = true }: ProfileCardProps) => (
     <div className="bg-white rounded-xl shadow-lg overflow-hidden max-w-sm mx-auto">
       <div className="h-64 bg-gradient-to-br from-pink-400 to-purple-500 flex items-center justify-center">
@@ -436,6 +504,7 @@ export default function SolDateDashboard() {
           <MapPin size={16} className="mr-1" />
           <span className="text-sm">{profile.location}</span>
         </div>
+        <TrustScore owner={profile.owner} />
         <p className="text-gray-700 mb-4">{profile.bio}</p>
         {profile.interests.length > 0 && (
           <div className="mb-4">
@@ -504,6 +573,17 @@ export default function SolDateDashboard() {
       }
     };
 
+    // Move the read cursor past the newest incoming message once it is on screen
+    const lastUnread = [...messages].reverse().find((message) => message.account.receiver.equals(publicKey!) && !message.account.seen);
+    useEffect(() => {
+      if (!lastUnread || !publicKey || programAccount.markRead.isPending) return;
+      programAccount.markRead.mutate({
+        userPubkey: publicKey,
+        conversation: lastUnread.account.conversation,
+        seq: lastUnread.account.seq,
+      });
+    }, [lastUnread?.account.seq.toString()]); // eslint-disable-line react-hooks/exhaustive-deps
+
     // Auto-focus input when chat opens
     useEffect(() => {
       if (messageInputRef.current) {
@@ -537,7 +617,17 @@ export default function SolDateDashboard() {
               <p className="text-sm text-gray-600">{match.otherUser.location}</p>
             </div>
           </div>
-          <div>
+          <div className="flex gap-2">
+            <Button
+            onClick={() => programAccount.sendTip.mutateAsync({ senderPubkey: publicKey!, receiverPubkey: match.otherUser.owner, sol: 0.01, note: null })}
+            disabled={programAccount.sendTip.isPending}
+            >
+              Tip 0.01 SOL</Button>
+            <Button
+            onClick={() => programAccount.reportUserProfile.mutateAsync({ reporterPubkey: publicKey!, reportedPubkey: match.otherUser.owner })}
+            disabled={programAccount.reportUserProfile.isPending}
+            >
+              Report</Button>
             <Button
             onClick={() => handleBlock(match.otherUser.owner)}
             >
@@ -550,7 +640,7 @@ export default function SolDateDashboard() {
             const isOwn = message.account.sender.equals(publicKey!);
             return (
               <div
-                key={message.publicKey.toString()}
+                key={message.account.seq.toString()}
                 className={`flex ${isOwn ? 'justify-end' : 'justify-start'}`}
               >
                 <div
@@ -560,9 +650,27 @@ export default function SolDateDashboard() {
                       : 'bg-gray-200 text-gray-800'
                   }`}
                 >
-                  <p>{message.account.content}</p>
+                  {message.account.deleted ? (
+                    <p className="italic opacity-70">Message deleted</p>
+                  ) : (
+                    <p>{programAccount.decryptMessage(message.account) ?? 'Encrypted message'}</p>
+                  )}
                   <p className="text-xs opacity-70 mt-1">
                     {new Date(message.account.timestamp.toNumber() * 1000).toLocaleTimeString()}
+                    {!message.account.editedAt.isZero() && ' · Edited'}
+                    {isOwn && message.account.seen && ' · Seen'}
+                    {!message.account.deleted && (
+                      <button
+                        onClick={() => programAccount.deleteMessage.mutate({
+                          userPubkey: publicKey!,
+                          conversation: message.account.conversation,
+                          seq: message.account.seq,
+                        })}
+                        className="ml-2 underline"
+                      >
+                        {isOwn ? 'Delete' : 'Hide'}
+                      </button>
+                    )}
                   </p>
                 </div>
               </div>
@@ -571,6 +679,17 @@ export default function SolDateDashboard() {
         </div>
 
         <div className="p-4 bg-white border-t mx-10 rounded-lg">
+          {currentUserProfile?.account.encryptionKeyVersion === 0 && (
+            <div className="flex items-center justify-between mb-2 text-sm text-gray-600">
+              <span>Messages are end-to-end encrypted, publish a key to start chatting.</span>
+              <Button
+                onClick={() => programAccount.publishEncryptionKey.mutateAsync({ userPubkey: publicKey! })}
+                disabled={programAccount.publishEncryptionKey.isPending}
+              >
+                Enable encryption
+              </Button>
+            </div>
+          )}
           <form onSubmit={handleSendMessage} className="flex gap-2">
             <input
               ref={messageInputRef}
@@ -841,7 +960,7 @@ export default function SolDateDashboard() {
                             profile.account.owner.equals(like.account.sender)
                           );
                           return (
-                            <div key={like.publicKey.toString()} className="flex items-center justify-between p-3 bg-pink-50 rounded-lg">
+                            <div key={like.publicKey.toString()} className={`flex items-center justify-between p-3 rounded-lg ${like.account.kind.super ? 'bg-yellow-50 ring-2 ring-yellow-300' : 'bg-pink-50'}`}>
                               <div className="flex items-center">
                                 <div className="w-10 h-10 bg-gradient-to-br from-pink-400 to-purple-500 rounded-full flex items-center justify-center mr-3">
                                   <User size={20} className="text-white" />
@@ -855,6 +974,11 @@ export default function SolDateDashboard() {
                                   </p>
                                 </div>
                               </div>
+                              {like.account.kind.super && !like.account.isMutual && (
+                                <span className="bg-yellow-100 text-yellow-800 px-2 py-1 rounded-full text-xs font-medium">
+                                  Super like
+                                </span>
+                              )}
                               {like.account.isMutual && (
                                 <span className="bg-green-100 text-green-800 px-2 py-1 rounded-full text-xs font-medium">
                                   Match!
@@ -1,15 +1,19 @@
 'use client'
 
+import { EventParser, Program } from '@coral-xyz/anchor'
 import { getSoldateProgram, getSoldateProgramId } from '@project/anchor'
-import { useConnection } from '@solana/wallet-adapter-react'
-import { Cluster, PublicKey, SystemProgram } from '@solana/web3.js'
+import type { Soldate } from '@project/anchor'
+import { useConnection, useWallet } from '@solana/wallet-adapter-react'
+import { Cluster, Connection, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from '@solana/web3.js'
+import BN from 'bn.js'
 import { useMutation, useQuery } from '@tanstack/react-query'
 import { useMemo } from 'react'
 import { useCluster } from '../cluster/cluster-data-access'
 import { useAnchorProvider } from '../solana/solana-provider'
 import { useTransactionToast } from '../use-transaction-toast'
 import { toast } from 'sonner'
-import { BN } from 'bn.js'
+import type nacl from 'tweetnacl'
+import { deriveEncryptionKeyPair, openMessage, sealMessage } from './soldate-encryption'
 
 interface CreateUserProfileArgs {
   name: string, 
@@ -34,17 +38,120 @@ interface BlockUserProfileArgs {
   toBlockPubkey: PublicKey
 }
 
+interface ReportUserProfileArgs {
+  reporterPubkey: PublicKey,
+  reportedPubkey: PublicKey
+}
+
+interface SendTipArgs {
+  senderPubkey: PublicKey,
+  receiverPubkey: PublicKey,
+  sol: number,
+  note: string | null
+}
+
 interface LikeUserProfileArgs {
   likedUserPubkey: PublicKey, 
   userPubkey: PublicKey
 }
 
+interface PublishEncryptionKeyArgs {
+  userPubkey: PublicKey
+}
+
+interface DecryptMessageArgs {
+  sender: PublicKey,
+  receiver: PublicKey,
+  keyVersion: number,
+  senderKeyVersion: number,
+  nonce: number[],
+  ciphertext: number[]
+}
+
+interface MarkReadArgs {
+  userPubkey: PublicKey,
+  conversation: PublicKey,
+  seq: BN
+}
+
+interface EditMessageArgs {
+  userPubkey: PublicKey,
+  toUserPubkey: PublicKey,
+  seq: BN,
+  content: string
+}
+
+interface DeleteMessageArgs {
+  userPubkey: PublicKey,
+  conversation: PublicKey,
+  seq: BN
+}
+
 interface SendMessagesArgs {
   userPubkey: PublicKey
   content: string, 
   toUserPubkey: PublicKey
 }
 
+const MESSAGES_PER_PAGE = 32;
+const MESSAGE_DELETED = 1 << 0;
+const MESSAGE_HIDDEN_BY_RECEIVER = 1 << 1;
+
+function findConfigPda(programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from("config")], programId)[0];
+}
+
+function findProfilePda(user: PublicKey, programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from("profile"), user.toBuffer()], programId)[0];
+}
+
+function findRecordPda(user: PublicKey, programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from("record"), user.toBuffer()], programId)[0];
+}
+
+function findBlockPda(blocker: PublicKey, blocked: PublicKey, programId: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("block"), blocker.toBuffer(), blocked.toBuffer()],
+    programId
+  )[0];
+}
+
+// Match accounts are seeded by the two owners in sorted order
+function findMatchPda(a: PublicKey, b: PublicKey, programId: PublicKey): PublicKey {
+  const [first, second] = Buffer.compare(a.toBuffer(), b.toBuffer()) < 0 ? [a, b] : [b, a];
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("match"), first.toBuffer(), second.toBuffer()],
+    programId
+  )[0];
+}
+
+function findConversationPda(a: PublicKey, b: PublicKey, programId: PublicKey): PublicKey {
+  const [first, second] = Buffer.compare(a.toBuffer(), b.toBuffer()) < 0 ? [a, b] : [b, a];
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("conversation"), first.toBuffer(), second.toBuffer()],
+    programId
+  )[0];
+}
+
+// The profile only holds the latest public key, older versions come from the `EncryptionKeyPublished`
+// events in the profile's history. Newest first, so a recreated profile's keys win over the old one's
+async function fetchPublishedKeys(program: Program<Soldate>, connection: Connection, owner: PublicKey): Promise<Map<number, Uint8Array>> {
+  const parser = new EventParser(program.programId, program.coder)
+  const keys = new Map<number, Uint8Array>()
+  const signatures = await connection.getSignaturesForAddress(findProfilePda(owner, program.programId))
+
+  for (const { signature } of signatures) {
+    const tx = await connection.getTransaction(signature, { maxSupportedTransactionVersion: 0 })
+    for (const event of parser.parseLogs(tx?.meta?.logMessages ?? [])) {
+      if (event.name !== 'encryptionKeyPublished' || !event.data.owner.equals(owner)) continue
+      if (!keys.has(event.data.keyVersion)) {
+        keys.set(event.data.keyVersion, Uint8Array.from(event.data.encryptionKey))
+      }
+    }
+  }
+  return keys
+}
+
 // Helper function to convert number to little-endian bytes
 function numberToLittleEndianBytes(num: number): Uint8Array {
   const buffer = new ArrayBuffer(8);
@@ -62,7 +169,11 @@ export function useSoldateProgram() {
   const programId = useMemo(() => getSoldateProgramId(cluster.network as Cluster), [cluster])
   const program = useMemo(() => getSoldateProgram(provider, programId), [provider, programId])
 
-  // Remove matchAccounts query as Match struct doesn't exist in contract
+  const matchAccounts = useQuery({
+    queryKey: ['match', 'all', { cluster }],
+    queryFn: () => program.account.match.all(),
+  })
+
   const blockUserAccounts = useQuery({
     queryKey: ['blockUser', 'all', { cluster }],
     queryFn: () => program.account.blockedUser.all(),
@@ -78,9 +189,53 @@ export function useSoldateProgram() {
     queryFn: () => program.account.userProfile.all(),
   })
 
+  // Reputation is kept per wallet so it survives a closed and recreated profile
+  const userRecordAccounts = useQuery({
+    queryKey: ['userRecord', 'all', { cluster }],
+    queryFn: () => program.account.userRecord.all(),
+  })
+
+  // Messages live in paged logs, flatten them into one entry per message
   const messageAccounts = useQuery({
     queryKey: ['message', 'all', { cluster }],
-    queryFn: () => program.account.messageAccount.all(),
+    queryFn: async () => {
+      const [conversations, pages] = await Promise.all([
+        program.account.conversation.all(),
+        program.account.messagePage.all(),
+      ])
+
+      return pages.flatMap(({ publicKey, account: page }) => {
+        const conversation = conversations.find(({ publicKey }) => publicKey.equals(page.conversation))
+        if (!conversation) return []
+
+        const participants = [conversation.account.userA, conversation.account.userB]
+        const readCursors = [conversation.account.readCursorA, conversation.account.readCursorB]
+        // Slots before a reopened page's first message were never written and have no timestamp
+        return page.entries.flatMap((entry, slot) => {
+          if (entry.timestamp.isZero()) return []
+          const seq = page.pageIndex.muln(MESSAGES_PER_PAGE).addn(slot)
+          return [{
+            publicKey,
+            account: {
+              conversation: conversation.publicKey,
+              sender: participants[entry.senderIndex],
+              receiver: participants[1 - entry.senderIndex],
+              seq,
+              keyVersion: entry.keyVersion,
+              senderKeyVersion: entry.senderKeyVersion,
+              nonce: entry.nonce,
+              ciphertext: entry.ciphertext.slice(0, entry.len),
+              timestamp: entry.timestamp,
+              editedAt: entry.editedAt,
+              deleted: (entry.flags & MESSAGE_DELETED) !== 0,
+              hiddenByReceiver: (entry.flags & MESSAGE_HIDDEN_BY_RECEIVER) !== 0,
+              // The receiver's read cursor has moved past this message
+              seen: seq.lt(readCursors[1 - entry.senderIndex]),
+            },
+          }]
+        })
+      })
+    },
   })
 
   const getProgramAccount = useQuery({
@@ -99,6 +254,7 @@ export function useSoldateProgram() {
       return await program.methods
         .createProfile(name, age, bio, interests, location)
         .accountsStrict({ 
+          config: findConfigPda(program.programId),
           user: userPubkey,
           profile: profilePDA,
           systemProgram: SystemProgram.programId
@@ -118,8 +274,10 @@ export function useSoldateProgram() {
     program,
     programId,
     likeAccounts,
+    matchAccounts,
     blockUserAccounts,
     userProfileAccounts,
+    userRecordAccounts,
     messageAccounts,
     getProgramAccount,
     createUserProfile,
@@ -127,15 +285,124 @@ export function useSoldateProgram() {
 }
 
 export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
+  const { connection } = useConnection()
   const { cluster } = useCluster()
   const transactionToast = useTransactionToast()
-  const { program, userProfileAccounts, likeAccounts, blockUserAccounts, messageAccounts } = useSoldateProgram()
+  const { program, userProfileAccounts, likeAccounts, matchAccounts, blockUserAccounts, messageAccounts } = useSoldateProgram()
+  const { signMessage } = useWallet()
 
   const accountQuery = useQuery({
     queryKey: ['userProfile', 'fetch', { cluster, account }],
     queryFn: () => program.account.userProfile.fetch(account),
   })
 
+  // Asks the wallet to sign once per key version, the derived key pair is kept for the session
+  const keyVersion = accountQuery.data?.encryptionKeyVersion ?? 0
+  const encryptionKeyPair = useQuery({
+    queryKey: ['encryption-key', { account, keyVersion }],
+    queryFn: () => deriveEncryptionKeyPair(signMessage!, keyVersion),
+    enabled: keyVersion > 0 && !!signMessage,
+    staleTime: Infinity,
+  })
+
+  // Each message was sealed with the sender's key at `senderKeyVersion` to the receiver's key at
+  // `keyVersion`, so which versions are ours and which are the other side's depends on the direction
+  const keyVersions = useMemo(() => {
+    const own = new Set<number>()
+    const others = new Map<string, { owner: PublicKey, versions: Set<number> }>()
+    for (const { account: message } of messageAccounts.data ?? []) {
+      const isSender = message.sender.equals(account)
+      if (!isSender && !message.receiver.equals(account)) continue
+
+      const other = isSender ? message.receiver : message.sender
+      const entry = others.get(other.toBase58()) ?? { owner: other, versions: new Set<number>() }
+      own.add(isSender ? message.senderKeyVersion : message.keyVersion)
+      entry.versions.add(isSender ? message.keyVersion : message.senderKeyVersion)
+      others.set(other.toBase58(), entry)
+    }
+    return { own: [...own].sort((a, b) => a - b), others: [...others.values()] }
+  }, [messageAccounts.data, account])
+
+  // Older own key pairs are derived again from the wallet, one signature per version
+  const pastKeyVersions = keyVersions.own.filter((version) => version > 0 && version !== keyVersion)
+  const pastKeyPairs = useQuery({
+    queryKey: ['encryption-key', 'past', { account, versions: pastKeyVersions }],
+    queryFn: async () => {
+      const keyPairs = new Map<number, nacl.BoxKeyPair>()
+      for (const version of pastKeyVersions) {
+        keyPairs.set(version, await deriveEncryptionKeyPair(signMessage!, version))
+      }
+      return keyPairs
+    },
+    enabled: pastKeyVersions.length > 0 && !!signMessage,
+    staleTime: Infinity,
+  })
+
+  // Only fetched for counterparts that have messages sealed with a key they since rotated
+  const currentKeyVersion = (owner: PublicKey) =>
+    userProfileAccounts.data?.find(({ account: profile }) => profile.owner.equals(owner))?.account.encryptionKeyVersion
+  const rotatedCounterparts = keyVersions.others
+    .filter(({ owner, versions }) => [...versions].some((version) => version !== currentKeyVersion(owner)))
+    .map(({ owner }) => owner)
+  const publishedKeys = useQuery({
+    queryKey: ['encryption-key', 'published', { cluster, owners: rotatedCounterparts.map((owner) => owner.toBase58()) }],
+    queryFn: async () => {
+      const keys = new Map<string, Map<number, Uint8Array>>()
+      for (const owner of rotatedCounterparts) {
+        keys.set(owner.toBase58(), await fetchPublishedKeys(program, connection, owner))
+      }
+      return keys
+    },
+    enabled: rotatedCounterparts.length > 0,
+    staleTime: Infinity,
+  })
+
+  const decryptMessage = ({ sender, receiver, keyVersion: receiverKeyVersion, senderKeyVersion, nonce, ciphertext }: DecryptMessageArgs): string | null => {
+    const isSender = sender.equals(account)
+    const other = isSender ? receiver : sender
+    const [ownVersion, otherVersion] = isSender ? [senderKeyVersion, receiverKeyVersion] : [receiverKeyVersion, senderKeyVersion]
+
+    const ownKeyPair = ownVersion === keyVersion ? encryptionKeyPair.data : pastKeyPairs.data?.get(ownVersion)
+    const otherProfile = userProfileAccounts.data?.find(({ account: profile }) => profile.owner.equals(other))
+    const otherKey = otherProfile?.account.encryptionKeyVersion === otherVersion
+      ? Uint8Array.from(otherProfile.account.encryptionKey)
+      : publishedKeys.data?.get(other.toBase58())?.get(otherVersion)
+    if (!ownKeyPair || !otherKey) return null
+
+    return openMessage(nonce, ciphertext, ownKeyPair.secretKey, otherKey)
+  }
+
+  const publishEncryptionKey = useMutation<string, Error, PublishEncryptionKeyArgs>({
+    mutationKey: ['profile', 'encryption-key', { cluster }],
+    mutationFn: async({ userPubkey }) => {
+      if (!signMessage) throw new Error('Wallet does not support message signing')
+      const keyPair = await deriveEncryptionKeyPair(signMessage, keyVersion + 1)
+
+      const [profilePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("profile"), userPubkey.toBuffer()],
+        program.programId
+      );
+
+      return await program.methods
+        .setEncryptionKey([...keyPair.publicKey])
+        .accountsStrict({
+          config: findConfigPda(program.programId),
+          user: userPubkey,
+          profile: profilePDA,
+          systemProgram: SystemProgram.programId
+        })
+        .rpc()
+      },
+    onSuccess: async (signature) => {
+      transactionToast(signature)
+      await accountQuery.refetch()
+      await userProfileAccounts.refetch()
+    },
+    onError: () => {
+      toast.error('Failed to publish encryption key')
+    },
+  })
+
   const updateUserProfile = useMutation<string, Error, UpdateUserProfileArgs>({
     mutationKey: ['profile', 'update', { cluster }],
     mutationFn: async({ name, age, bio, interests, location, userPubkey }) => {
@@ -153,6 +420,7 @@ export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
           location ?? null
         )
         .accountsStrict({ 
+          config: findConfigPda(program.programId),
           user: userPubkey,
           profile: profilePDA,
           systemProgram: SystemProgram.programId
@@ -194,6 +462,100 @@ export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
     },
   })
 
+  const unblockUserProfile = useMutation<string, Error, BlockUserProfileArgs>({
+    mutationKey: ['profile', 'unblock', { cluster }],
+    mutationFn: async({ blockerPubkey, toBlockPubkey }) => {
+      return await program.methods
+        .unblockUser(toBlockPubkey)
+        .accountsStrict({ 
+          blocker: blockerPubkey,
+          block: findBlockPda(blockerPubkey, toBlockPubkey, program.programId),
+          systemProgram: SystemProgram.programId
+        })
+        .rpc()
+      },
+    onSuccess: async (signature) => {
+      transactionToast(signature)
+      await blockUserAccounts.refetch()
+    },
+    onError: () => {
+      toast.error('Failed to unblock user profile')
+    },
+  })
+
+  const reportUserProfile = useMutation<string, Error, ReportUserProfileArgs>({
+    mutationKey: ['profile', 'report', { cluster }],
+    mutationFn: async({ reporterPubkey, reportedPubkey }) => {
+      const [reportPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("report"), reporterPubkey.toBuffer(), reportedPubkey.toBuffer()],
+        program.programId
+      );
+
+      return await program.methods
+        .reportUser(reportedPubkey, { harassment: {} }, null)
+        .accountsStrict({
+          reporter: reporterPubkey,
+          reporterProfile: PublicKey.findProgramAddressSync(
+            [Buffer.from("profile"), reporterPubkey.toBuffer()],
+            program.programId
+          )[0],
+          reportedProfile: PublicKey.findProgramAddressSync(
+            [Buffer.from("profile"), reportedPubkey.toBuffer()],
+            program.programId
+          )[0],
+          reportedRecord: findRecordPda(reportedPubkey, program.programId),
+          report: reportPda,
+          conversation: null,
+          page: null,
+          systemProgram: SystemProgram.programId
+        })
+        .rpc()
+      },
+    onSuccess: async (signature) => {
+      transactionToast(signature)
+      await userProfileAccounts.refetch()
+    },
+    onError: () => {
+      toast.error('Failed to report user')
+    },
+  })
+
+  const sendTip = useMutation<string, Error, SendTipArgs>({
+    mutationKey: ['profile', 'tip', { cluster }],
+    mutationFn: async({ senderPubkey, receiverPubkey, sol, note }) => {
+      return await program.methods
+        .sendTip(new BN(Math.round(sol * LAMPORTS_PER_SOL)), note)
+        .accountsStrict({
+          sender: senderPubkey,
+          config: findConfigPda(program.programId),
+          senderProfile: PublicKey.findProgramAddressSync(
+            [Buffer.from("profile"), senderPubkey.toBuffer()],
+            program.programId
+          )[0],
+          receiverProfile: PublicKey.findProgramAddressSync(
+            [Buffer.from("profile"), receiverPubkey.toBuffer()],
+            program.programId
+          )[0],
+          receiver: receiverPubkey,
+          senderBlock: findBlockPda(senderPubkey, receiverPubkey, program.programId),
+          receiverBlock: findBlockPda(receiverPubkey, senderPubkey, program.programId),
+          matchAccount: findMatchPda(senderPubkey, receiverPubkey, program.programId),
+          mint: null,
+          senderTokenAccount: null,
+          receiverTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId
+        })
+        .rpc()
+      },
+    onSuccess: (signature) => {
+      transactionToast(signature)
+    },
+    onError: () => {
+      toast.error('Failed to send tip')
+    },
+  })
+
   const likeUserProfile = useMutation<string, Error, LikeUserProfileArgs>({
     mutationKey: ['profile', 'like', { cluster }],
     mutationFn: async({ likedUserPubkey, userPubkey }) => {
@@ -231,16 +593,11 @@ export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
         program.programId
       );
 
-      // Check if reverse like account exists
-      const remainingAccounts = [];
+      // The reverse like is always passed, the match only when it is about to be created
+      let matchPda: PublicKey | null = null;
       try {
         await program.account.like.fetch(reverseLikePda);
-        // If it exists, add it to remaining accounts
-        remainingAccounts.push({
-          pubkey: reverseLikePda,
-          isWritable: false,
-          isSigner: false,
-        });
+        matchPda = findMatchPda(userPubkey, likedUserPubkey, program.programId);
       } catch (error) {
         // Reverse like doesn't exist, that's okay
         console.log(error);
@@ -250,13 +607,17 @@ export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
       const signature = await program.methods
         .sendLike(likedUserPubkey)
         .accountsStrict({ 
+          config: findConfigPda(program.programId),
           sender: userPubkey,
           senderProfile: senderProfilePda,
           targetProfile: targetProfilePda,
           like: likePda,
+          reverseLike: reverseLikePda,
+          senderBlock: findBlockPda(userPubkey, likedUserPubkey, program.programId),
+          targetBlock: findBlockPda(likedUserPubkey, userPubkey, program.programId),
+          matchAccount: matchPda,
           systemProgram: SystemProgram.programId
         })
-        .remainingAccounts(remainingAccounts)
         .rpc();
 
       return signature;
@@ -264,12 +625,15 @@ export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
     onSuccess: async (signature) => {
       transactionToast(signature)
       await likeAccounts.refetch()
+      await matchAccounts.refetch()
       await userProfileAccounts.refetch()
     },
     onError: (error) => {
       console.error('Like error:', error)
       if (error.message === 'You have already liked this user') {
         toast.error('You have already liked this user')
+      } else if (error.message.includes('DailyLikeLimitReached')) {
+        toast.error('You have used all your likes for today, come back tomorrow')
       } else {
         toast.error('Failed to like user profile')
       }
@@ -290,78 +654,170 @@ export function useSoldateProgramAccount({ account }: { account: PublicKey }) {
         program.programId
       );
 
-      // Generate message ID (timestamp based like in test)
-      const messageId = Math.floor(Date.now() / 1000);
-      
-      // Create message PDA using the same structure as test
-      // const messageIdBuffer = Buffer.allocUnsafe(8);
-      // messageIdBuffer.writeBigUInt64LE(BigInt(messageId), 0);
-      const messageIdBuffer = Buffer.from(numberToLittleEndianBytes(messageId));
-
+      // Messages are sealed to the receiver's currently published key
+      const payload = await sealTo(receiverProfilePda, content);
 
-      /**
-       * TODO: Solve above 
-       */
+      // Messages are appended to the page holding the conversation's next sequence number
+      const conversationPda = findConversationPda(userPubkey, toUserPubkey, program.programId);
+      const conversation = await program.account.conversation.fetchNullable(conversationPda);
+      const seq = conversation ? conversation.nextSeq.toNumber() : 0;
 
-      const [messagePda] = PublicKey.findProgramAddressSync(
+      const [pagePda] = PublicKey.findProgramAddressSync(
         [
-          Buffer.from("message"),
-          userPubkey.toBuffer(),
-          toUserPubkey.toBuffer(),
-          messageIdBuffer
+          Buffer.from("page"),
+          conversationPda.toBuffer(),
+          Buffer.from(numberToLittleEndianBytes(Math.floor(seq / MESSAGES_PER_PAGE)))
         ],
         program.programId
       );
 
-      // Get like PDAs for remaining accounts (required by contract)
-      const [senderLikePda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("like"), userPubkey.toBuffer(), toUserPubkey.toBuffer()],
+      return await program.methods
+        .sendMessage(payload)
+        .accountsStrict({ 
+          config: findConfigPda(program.programId),
+          sender: userPubkey,
+          senderProfile: senderProfilePda,
+          receiverProfile: receiverProfilePda,
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(userPubkey, toUserPubkey, program.programId),
+          receiverBlock: findBlockPda(toUserPubkey, userPubkey, program.programId),
+          matchAccount: findMatchPda(userPubkey, toUserPubkey, program.programId),
+          systemProgram: SystemProgram.programId
+        })
+        .rpc()
+    },
+    onSuccess: async (signature) => {
+      transactionToast(signature)
+      await messageAccounts.refetch()
+    },
+    onError: (error) => {
+      console.error('Send message error:', error)
+      toast.error('Failed to send message')
+    },
+  })
+
+  // Looks up the receiver's current key and seals `content` to it
+  const sealTo = async (receiverProfilePda: PublicKey, content: string) => {
+    if (!encryptionKeyPair.data) throw new Error('Publish an encryption key before messaging')
+    const receiverProfile = await program.account.userProfile.fetch(receiverProfilePda);
+    return sealMessage(
+      content,
+      encryptionKeyPair.data.secretKey,
+      Uint8Array.from(receiverProfile.encryptionKey),
+      receiverProfile.encryptionKeyVersion
+    );
+  }
+
+  const editMessage = useMutation<string, Error, EditMessageArgs>({
+    mutationKey: ['message', 'edit', { cluster }],
+    mutationFn: async({ userPubkey, toUserPubkey, seq, content }) => {
+      const [senderProfilePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("profile"), userPubkey.toBuffer()],
+        program.programId
+      );
+
+      const [receiverProfilePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("profile"), toUserPubkey.toBuffer()],
         program.programId
       );
 
-      const [receiverLikePda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("like"), toUserPubkey.toBuffer(), userPubkey.toBuffer()],
+      const conversationPda = findConversationPda(userPubkey, toUserPubkey, program.programId);
+      const [pagePda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("page"),
+          conversationPda.toBuffer(),
+          Buffer.from(numberToLittleEndianBytes(Math.floor(seq.toNumber() / MESSAGES_PER_PAGE)))
+        ],
         program.programId
       );
 
       return await program.methods
-        .sendMessage(new BN(messageId), content)
-        .accountsStrict({ 
+        .editMessage(seq, await sealTo(receiverProfilePda, content))
+        .accountsStrict({
+          config: findConfigPda(program.programId),
           sender: userPubkey,
           senderProfile: senderProfilePda,
           receiverProfile: receiverProfilePda,
-          message: messagePda,
-          systemProgram: SystemProgram.programId
+          conversation: conversationPda,
+          page: pagePda,
+          senderBlock: findBlockPda(userPubkey, toUserPubkey, program.programId),
+          receiverBlock: findBlockPda(toUserPubkey, userPubkey, program.programId),
         })
-        .remainingAccounts([
-          {
-            pubkey: senderLikePda,
-            isWritable: false,
-            isSigner: false,
-          },
-          {
-            pubkey: receiverLikePda,
-            isWritable: false,
-            isSigner: false,
-          }
-        ])
         .rpc()
     },
     onSuccess: async (signature) => {
       transactionToast(signature)
       await messageAccounts.refetch()
     },
+    onError: () => {
+      toast.error('Failed to edit message')
+    },
+  })
+
+  // Deletes the caller's own messages, or hides a received one from their view
+  const deleteMessage = useMutation<string, Error, DeleteMessageArgs>({
+    mutationKey: ['message', 'delete', { cluster }],
+    mutationFn: async({ userPubkey, conversation, seq }) => {
+      const [pagePda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("page"),
+          conversation.toBuffer(),
+          Buffer.from(numberToLittleEndianBytes(Math.floor(seq.toNumber() / MESSAGES_PER_PAGE)))
+        ],
+        program.programId
+      );
+
+      return await program.methods
+        .deleteMessage(seq)
+        .accountsStrict({
+          user: userPubkey,
+          conversation,
+          page: pagePda,
+        })
+        .rpc()
+    },
+    onSuccess: async (signature) => {
+      transactionToast(signature)
+      await messageAccounts.refetch()
+    },
+    onError: () => {
+      toast.error('Failed to delete message')
+    },
+  })
+
+  const markRead = useMutation<string, Error, MarkReadArgs>({
+    mutationKey: ['message', 'read', { cluster }],
+    mutationFn: async({ userPubkey, conversation, seq }) => {
+      return await program.methods
+        .markRead(seq)
+        .accountsStrict({
+          reader: userPubkey,
+          conversation,
+        })
+        .rpc()
+    },
+    onSuccess: async () => {
+      await messageAccounts.refetch()
+    },
     onError: (error) => {
-      console.error('Send message error:', error)
-      toast.error('Failed to send message')
+      console.error('Mark read error:', error)
     },
   })
 
   return {
     accountQuery,
     blockUserProfile,
+    unblockUserProfile,
+    reportUserProfile,
+    sendTip,
     updateUserProfile,
     likeUserProfile,
+    publishEncryptionKey,
+    decryptMessage,
+    markRead,
+    editMessage,
+    deleteMessage,
     sendMessages
   }
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+import nacl from 'tweetnacl'
+
+// Must match `MESSAGE_FORMAT_VERSION` and the box used by the program's `crypto` module
+export const MESSAGE_FORMAT_VERSION = 1
+
+export interface EncryptedPayload {
+  version: number
+  keyVersion: number
+  nonce: number[]
+  ciphertext: Buffer
+}
+
+// The key pair is derived from a wallet signature, so it never has to be stored and rotating
+// the key is just signing for the next version
+export async function deriveEncryptionKeyPair(
+  signMessage: (message: Uint8Array) => Promise<Uint8Array>,
+  keyVersion: number,
+): Promise<nacl.BoxKeyPair> {
+  const signature = await signMessage(Buffer.from(`SolDate encryption key v${keyVersion}`))
+  return nacl.box.keyPair.fromSecretKey(nacl.hash(signature).slice(0, nacl.box.secretKeyLength))
+}
+
+export function sealMessage(
+  text: string,
+  senderSecretKey: Uint8Array,
+  receiverPublicKey: Uint8Array,
+  receiverKeyVersion: number,
+): EncryptedPayload {
+  const nonce = nacl.randomBytes(nacl.box.nonceLength)
+  const ciphertext = nacl.box(Buffer.from(text), nonce, receiverPublicKey, senderSecretKey)
+
+  return {
+    version: MESSAGE_FORMAT_VERSION,
+    keyVersion: receiverKeyVersion,
+    nonce: [...nonce],
+    ciphertext: Buffer.from(ciphertext),
+  }
+}
+
+// Returns null if the message was sealed to a key the caller does not hold
+export function openMessage(
+  nonce: number[],
+  ciphertext: number[],
+  ownSecretKey: Uint8Array,
+  otherPublicKey: Uint8Array,
+): string | null {
+  const plaintext = nacl.box.open(Uint8Array.from(ciphertext), Uint8Array.from(nonce), otherPublicKey, ownSecretKey)
+  return plaintext ? Buffer.from(plaintext).toString() : null
+}